@@ -0,0 +1,28 @@
+use std::env::current_dir;
+use std::fs::create_dir_all;
+
+use alliance_cosmwasm::{
+    AllianceAsset, AllianceMsg, AllianceQuery, DecCoin, Delegation, Pagination, PaginationResponse, ValidatorResponse,
+};
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for, QueryResponses};
+
+fn main() {
+    let mut out_dir = current_dir().unwrap();
+    out_dir.push("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    export_schema(&schema_for!(AllianceMsg), &out_dir);
+    export_schema(&schema_for!(AllianceQuery), &out_dir);
+    export_schema(&schema_for!(AllianceAsset), &out_dir);
+    export_schema(&schema_for!(ValidatorResponse), &out_dir);
+    export_schema(&schema_for!(Delegation), &out_dir);
+    export_schema(&schema_for!(DecCoin), &out_dir);
+    export_schema(&schema_for!(Pagination), &out_dir);
+    export_schema(&schema_for!(PaginationResponse), &out_dir);
+
+    // One schema per query response, named after the query it answers
+    for (query, schema) in AllianceQuery::response_schemas().unwrap() {
+        export_schema_with_title(&schema, &out_dir, &format!("{}_response", query));
+    }
+}
@@ -0,0 +1,242 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use cosmwasm_std::StdResult;
+
+use crate::{
+    AllianceQuerier, AllianceResponse, AlliancesDelegationsResponse, AlliancesResponse, DelegationResponse,
+    DelegationRewardsResponse, DelegatorAddr, Pagination, ParamsResponse, RewardPoolResponse, RewardRateResponse,
+    UnbondingDelegationsResponse, ValidatorAddr, ValidatorResponse, ValidatorsResponse,
+};
+
+/// Wraps an `AllianceQuerier` and memoizes `Params`, `Validator` and `Alliance` responses for
+/// its lifetime, so repeated identical queries within one execution only pay gas once.
+/// Errors are not cached, and all other queries are passed through unchanged.
+pub struct CachingAllianceQuerier<Q: AllianceQuerier> {
+    inner: Q,
+    params: RefCell<Option<ParamsResponse>>,
+    validators: RefCell<HashMap<ValidatorAddr, ValidatorResponse>>,
+    alliances: RefCell<HashMap<String, AllianceResponse>>,
+}
+
+impl<Q: AllianceQuerier> CachingAllianceQuerier<Q> {
+    pub fn new(inner: Q) -> Self {
+        CachingAllianceQuerier {
+            inner,
+            params: RefCell::new(None),
+            validators: RefCell::new(HashMap::new()),
+            alliances: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn inner(&self) -> &Q {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Q {
+        self.inner
+    }
+}
+
+impl<Q: AllianceQuerier> AllianceQuerier for CachingAllianceQuerier<Q> {
+    fn query_alliance_alliance(
+        &self,
+        denom: String,
+    ) -> StdResult<AllianceResponse> {
+        if let Some(res) = self.alliances.borrow().get(&denom) {
+            return Ok(res.clone());
+        }
+        let res = self.inner.query_alliance_alliance(denom.clone())?;
+        self.alliances.borrow_mut().insert(denom, res.clone());
+        Ok(res)
+    }
+
+    fn query_alliance_alliances(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesResponse> {
+        self.inner.query_alliance_alliances(pagination)
+    }
+
+    fn query_alliance_alliances_delegations(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.inner.query_alliance_alliances_delegations(pagination)
+    }
+
+    fn query_alliance_alliances_delegations_by_delegator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.inner.query_alliance_alliances_delegations_by_delegator(delegator_addr, pagination)
+    }
+
+    fn query_alliance_alliances_delegation_by_validator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.inner.query_alliance_alliances_delegation_by_validator(delegator_addr, validator_addr, pagination)
+    }
+
+    fn query_alliance_delegation(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationResponse> {
+        self.inner.query_alliance_delegation(delegator_addr, validator_addr, denom)
+    }
+
+    fn query_alliance_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationRewardsResponse> {
+        self.inner.query_alliance_delegation_rewards(delegator_addr, validator_addr, denom)
+    }
+
+    fn query_alliance_params(
+        &self,
+    ) -> StdResult<ParamsResponse> {
+        if let Some(res) = self.params.borrow().as_ref() {
+            return Ok(res.clone());
+        }
+        let res = self.inner.query_alliance_params()?;
+        *self.params.borrow_mut() = Some(res.clone());
+        Ok(res)
+    }
+
+    fn query_alliance_validator(
+        &self,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<ValidatorResponse> {
+        if let Some(res) = self.validators.borrow().get(&validator_addr) {
+            return Ok(res.clone());
+        }
+        let res = self.inner.query_alliance_validator(validator_addr.clone())?;
+        self.validators.borrow_mut().insert(validator_addr, res.clone());
+        Ok(res)
+    }
+
+    fn query_alliance_validators(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<ValidatorsResponse> {
+        self.inner.query_alliance_validators(pagination)
+    }
+
+    fn query_alliance_all_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<DelegationRewardsResponse> {
+        self.inner.query_alliance_all_delegation_rewards(delegator_addr, validator_addr)
+    }
+
+    fn query_alliance_reward_pool(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardPoolResponse> {
+        self.inner.query_alliance_reward_pool(denom)
+    }
+
+    fn query_alliance_unbonding_delegations(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<UnbondingDelegationsResponse> {
+        self.inner.query_alliance_unbonding_delegations(delegator_addr, pagination)
+    }
+
+    fn query_alliance_alliance_delegation_rewards_rate(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardRateResponse> {
+        self.inner.query_alliance_alliance_delegation_rewards_rate(denom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockAllianceQuerier;
+    use crate::fixtures::{params, validator};
+    use crate::AllianceQueryWrapper;
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{ContractResult, QuerierWrapper, SystemResult};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A querier answering from `mock` that counts the queries reaching it
+    fn counting(mock: MockAllianceQuerier) -> (MockQuerier<AllianceQueryWrapper>, Rc<Cell<u32>>) {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let querier = MockQuerier::new(&[]).with_custom_handler(move |query: &AllianceQueryWrapper| {
+            counter.set(counter.get() + 1);
+            mock.handle(&query.0)
+        });
+        (querier, calls)
+    }
+
+    #[test]
+    fn params_are_queried_once() {
+        let (querier, calls) = counting(MockAllianceQuerier::new().with_params(params()));
+        let querier = CachingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier));
+        for _ in 0..3 {
+            assert_eq!(querier.query_alliance_params(), Ok(ParamsResponse { params: params() }));
+        }
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[test]
+    fn errors_are_not_cached() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let mock = MockAllianceQuerier::new().with_params(params());
+        let querier = MockQuerier::new(&[]).with_custom_handler(move |query: &AllianceQueryWrapper| {
+            counter.set(counter.get() + 1);
+            match counter.get() {
+                1 => SystemResult::Ok(ContractResult::Err("temporarily unavailable".to_string())),
+                _ => mock.handle(&query.0),
+            }
+        });
+        let querier = CachingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier));
+        querier.query_alliance_params().unwrap_err();
+        querier.query_alliance_params().unwrap();
+        querier.query_alliance_params().unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn validators_and_alliances_are_cached_per_key() {
+        let mock = MockAllianceQuerier::new()
+            .with_validator(validator("cosmosvaloper1a"))
+            .with_validator(validator("cosmosvaloper1b"));
+        let (querier, calls) = counting(mock);
+        let querier = CachingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier));
+        for _ in 0..2 {
+            assert_eq!(querier.query_alliance_validator("cosmosvaloper1a".into()), Ok(validator("cosmosvaloper1a")));
+            assert_eq!(querier.query_alliance_validator("cosmosvaloper1b".into()), Ok(validator("cosmosvaloper1b")));
+        }
+        assert_eq!(calls.get(), 2);
+
+        // Unknown alliances fail every time rather than being remembered as missing
+        querier.query_alliance_alliance("uluna".to_string()).unwrap_err();
+        querier.query_alliance_alliance("uluna".to_string()).unwrap_err();
+        assert_eq!(calls.get(), 4);
+    }
+
+    #[test]
+    fn other_queries_pass_through() {
+        let (querier, calls) = counting(MockAllianceQuerier::new());
+        let querier = CachingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier));
+        querier.query_alliance_validators(None).unwrap();
+        querier.query_alliance_validators(None).unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+}
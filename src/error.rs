@@ -0,0 +1,127 @@
+use cosmwasm_std::{CheckedFromRatioError, ConversionOverflowError, OverflowError, StdError};
+use thiserror::Error;
+
+/// Alliance-specific failures. The helpers in this crate return it directly so callers can match
+/// on the failure mode; it converts into a `StdError` for use with `?` in `StdResult` code.
+#[derive(Error, Debug, PartialEq)]
+pub enum AllianceError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("{0}")]
+    ConversionOverflow(#[from] ConversionOverflowError),
+
+    #[error("{0}")]
+    CheckedFromRatio(#[from] CheckedFromRatioError),
+
+    #[error("Expected a denom but none was set")]
+    MissingDenom {},
+
+    #[error("Invalid denom: {denom}")]
+    InvalidDenom { denom: String },
+
+    #[error("Invalid validator address: {address}")]
+    InvalidValidatorAddress { address: String },
+
+    #[error("Timestamp {timestamp} is out of range")]
+    TimestampOutOfRange { timestamp: String },
+
+    #[error("Decimal {value} does not fit into the target integer type")]
+    DecimalOverflow { value: String },
+
+    #[error("No protobuf encoding for {msg}")]
+    UnsupportedProtoMessage { msg: String },
+
+    #[error("Cannot redelegate from validator {validator} to itself")]
+    RedelegateToSameValidator { validator: String },
+
+    #[error("Amount must be greater than zero")]
+    ZeroAmount {},
+
+    #[error("Invalid weight range: min {min} is greater than max {max}")]
+    InvalidWeightRange { min: String, max: String },
+
+    #[error("Weights must be non-empty and sum to more than zero")]
+    InvalidWeights {},
+
+    #[error("Validator has no shares for denom {denom}")]
+    ZeroShares { denom: String },
+
+    #[error("Amount {amount} is not a whole number of tokens")]
+    NonIntegerAmount { amount: String },
+}
+
+/// Errors wrapped from cosmwasm are passed through unchanged; alliance-specific ones become a
+/// `GenericErr` carrying their message.
+impl From<AllianceError> for StdError {
+    fn from(err: AllianceError) -> Self {
+        match err {
+            AllianceError::Std(err) => err,
+            AllianceError::Overflow(err) => err.into(),
+            AllianceError::ConversionOverflow(err) => err.into(),
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::{OverflowOperation, Uint128, Uint256};
+
+    #[test]
+    fn messages_name_the_offending_value() {
+        let cases = [
+            (AllianceError::MissingDenom {}, "Expected a denom but none was set"),
+            (AllianceError::InvalidDenom { denom: "1uluna".to_string() }, "Invalid denom: 1uluna"),
+            (
+                AllianceError::InvalidValidatorAddress { address: "cosmos1abc".to_string() },
+                "Invalid validator address: cosmos1abc",
+            ),
+            (
+                AllianceError::TimestampOutOfRange { timestamp: "1500-01-01T00:00:00Z".to_string() },
+                "Timestamp 1500-01-01T00:00:00Z is out of range",
+            ),
+            (
+                AllianceError::DecimalOverflow { value: "1e40".to_string() },
+                "Decimal 1e40 does not fit into the target integer type",
+            ),
+            (
+                AllianceError::RedelegateToSameValidator { validator: "cosmosvaloper1a".to_string() },
+                "Cannot redelegate from validator cosmosvaloper1a to itself",
+            ),
+            (
+                AllianceError::InvalidWeightRange { min: "0.2".to_string(), max: "0.1".to_string() },
+                "Invalid weight range: min 0.2 is greater than max 0.1",
+            ),
+            (AllianceError::ZeroShares { denom: "uluna".to_string() }, "Validator has no shares for denom uluna"),
+            (AllianceError::NonIntegerAmount { amount: "1.5".to_string() }, "Amount 1.5 is not a whole number of tokens"),
+        ];
+        for (err, message) in cases {
+            assert_eq!(err.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn alliance_errors_become_generic_std_errors() {
+        assert_eq!(
+            StdError::from(AllianceError::InvalidDenom { denom: "1uluna".to_string() }),
+            StdError::generic_err("Invalid denom: 1uluna")
+        );
+    }
+
+    #[test]
+    fn wrapped_cosmwasm_errors_pass_through() {
+        let err = || StdError::not_found("alliance");
+        assert_eq!(StdError::from(AllianceError::from(err())), err());
+
+        let overflow = || OverflowError::new(OverflowOperation::Add, Uint128::MAX, 1u128);
+        assert_eq!(StdError::from(AllianceError::from(overflow())), StdError::from(overflow()));
+
+        let conversion = || Uint128::try_from(Uint256::MAX).unwrap_err();
+        assert_eq!(StdError::from(AllianceError::from(conversion())), StdError::from(conversion()));
+    }
+}
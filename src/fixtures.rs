@@ -0,0 +1,77 @@
+//! Sample values shared by the unit tests. Not every feature set runs every test module, so some
+//! of these go unused in a given build.
+#![allow(dead_code)]
+
+use std::str::FromStr;
+
+use cosmwasm_std::{Addr, Coin, Decimal256, Timestamp};
+
+use crate::{
+    AllianceAsset, AllianceParams, DecCoin, Delegation, DurationSecs, Reward, SingleDelegationResponse,
+    ValidatorResponse, WeightRange,
+};
+
+pub(crate) fn dec(s: &str) -> Decimal256 {
+    Decimal256::from_str(s).unwrap()
+}
+
+pub(crate) fn dec_coin(denom: Option<&str>, amount: &str) -> DecCoin {
+    DecCoin { denom: denom.map(str::to_string), amount: dec(amount) }
+}
+
+pub(crate) fn reward(denom: Option<&str>, index: &str) -> Option<Reward> {
+    Some(Reward { denom: denom.map(str::to_string), index: dec(index) })
+}
+
+pub(crate) fn weight_range() -> WeightRange {
+    WeightRange { min: dec("0.05"), max: dec("0.2") }
+}
+
+pub(crate) fn params() -> AllianceParams {
+    AllianceParams {
+        reward_delay_time: DurationSecs::from_secs(86_400),
+        take_rate_claim_interval: DurationSecs::from_secs(300),
+        last_take_rate_claim_time: Timestamp::from_seconds(1_686_076_649),
+    }
+}
+
+pub(crate) fn alliance_asset(denom: &str) -> AllianceAsset {
+    AllianceAsset {
+        denom: denom.to_string(),
+        reward_weight: dec("0.1"),
+        consensus_weight: dec("0.5"),
+        consensus_cap: Some(dec("0.3")),
+        take_rate: dec("0.005"),
+        total_tokens: dec("1000000"),
+        total_validator_shares: dec("999999.5"),
+        reward_start_time: Timestamp::from_nanos(1_686_076_649_956_787_974),
+        reward_change_rate: dec("0.99"),
+        reward_change_interval: DurationSecs::from_secs(86_400),
+        last_reward_change_time: Timestamp::from_nanos(1_686_163_049_956_787_974),
+        reward_weight_range: weight_range(),
+        is_initialized: Some(true),
+    }
+}
+
+pub(crate) fn validator(addr: &str) -> ValidatorResponse {
+    ValidatorResponse {
+        validator_addr: Addr::unchecked(addr),
+        total_delegation_shares: vec![dec_coin(Some("uluna"), "100.5")],
+        validator_shares: vec![dec_coin(Some("uluna"), "50")],
+        total_staked: vec![dec_coin(Some("uluna"), "200")],
+    }
+}
+
+pub(crate) fn delegation(delegator: &str, validator: &str, denom: &str, amount: u128) -> SingleDelegationResponse {
+    SingleDelegationResponse {
+        delegation: Delegation {
+            delegator_address: Some(Addr::unchecked(delegator)),
+            validator_address: Some(Addr::unchecked(validator)),
+            denom: Some(denom.to_string()),
+            shares: Decimal256::from_ratio(amount, 1u128),
+            reward_history: Some(vec![reward(Some("uluna"), "0.25"), None]),
+            last_reward_claim_height: Some(4_200_000),
+        },
+        balance: Coin::new(amount, denom),
+    }
+}
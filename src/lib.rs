@@ -1,121 +1,449 @@
-use chrono::{DateTime, Utc, TimeZone};
-use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, CustomQuery, QuerierWrapper, Decimal256, Coin, Binary, Timestamp, StdResult};
-use serde::{Serializer, Deserializer, Serialize, Deserialize};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal256, Coin, Binary, Timestamp, StdResult, Uint64, Uint128, Uint256};
+use schemars::JsonSchema;
+use serde::{Serialize, Serializer, Deserializer, Deserialize, de};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
 
-/// A number of Custom messages that can call into the Alliance bindings
-#[cw_serde]
-pub enum AllianceMsg {
-    Delegate {
-        delegator_address: Addr,
-        validator_address: Addr,
-        amount: Coin,
-    },
-    Undelegate {
-        delegator_address: Addr,
-        validator_address: Addr,
-        amount: Coin,
-    },
-    Redelegate {
-        delegator_address: Addr,
-        validator_src_address: Addr,
-        validator_dst_address: Addr,
-        amount: Coin,
-    },
-    ClaimDelegationRewards {
-        delegator_address: Addr,
-        validator_address: Addr,
-        denom: String,
-    },
-}
-
-/// Alliance-specific queries
+#[cfg(feature = "queries")]
+mod caching;
+mod error;
+#[cfg(test)]
+mod fixtures;
+#[cfg(feature = "rfc3339-time")]
+mod rfc3339;
+#[cfg(any(feature = "testing", all(test, feature = "queries")))]
+pub mod mock;
+#[cfg(feature = "messages")]
+mod msg;
+#[cfg(feature = "proto")]
+mod proto;
+#[cfg(feature = "queries")]
+mod query;
+#[cfg(feature = "queries")]
+mod retrying;
+
+#[cfg(feature = "queries")]
+pub use caching::CachingAllianceQuerier;
+pub use error::AllianceError;
+#[cfg(feature = "messages")]
+pub use msg::*;
+#[cfg(feature = "queries")]
+pub use query::*;
+#[cfg(feature = "queries")]
+pub use retrying::RetryingAllianceQuerier;
+
+/// A delegator account address. Kept distinct from `ValidatorAddr` so the two cannot be swapped
+/// by accident in message and query constructors, as long as callers pass the typed values rather
+/// than plain `Addr`s, which convert into either.
 #[cw_serde]
-#[derive(QueryResponses)]
-pub enum AllianceQuery {
-    #[returns(AllianceResponse)]
-    Alliance { denom: String },
+#[derive(Eq, Hash)]
+pub struct DelegatorAddr(Addr);
+
+impl DelegatorAddr {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
 
-    #[returns(AlliancesResponse)]
-    Alliances { pagination: Option<Pagination> },
+    pub fn as_addr(&self) -> &Addr {
+        &self.0
+    }
 
-    #[returns(AlliancesDelegationsResponse)]
-    AlliancesDelegations { pagination: Option<Pagination> },
+    pub fn into_addr(self) -> Addr {
+        self.0
+    }
+}
 
-    #[returns(AlliancesDelegationsResponse)]
-    AlliancesDelegationByValidator {
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        pagination: Option<Pagination>,
-    },
+impl fmt::Display for DelegatorAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
 
-    #[returns(DelegationResponse)]
-    Delegation {
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    },
+impl From<Addr> for DelegatorAddr {
+    fn from(addr: Addr) -> Self {
+        DelegatorAddr(addr)
+    }
+}
 
-    #[returns(DelegationRewardsResponse)]
-    DelegationRewards {
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    },
+impl From<DelegatorAddr> for Addr {
+    fn from(addr: DelegatorAddr) -> Self {
+        addr.0
+    }
+}
 
-    #[returns(ParamsResponse)]
-    Params {},
+/// A validator operator address (e.g. `cosmosvaloper1...`).
+/// These are not account addresses, so they are kept as a plain string rather than an `Addr`.
+#[cw_serde]
+#[derive(Eq, Hash)]
+pub struct ValidatorAddr(String);
 
-    #[returns(ValidatorResponse)]
-    Validator { validator_addr: Addr },
+impl ValidatorAddr {
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
 
-    #[returns(ValidatorsResponse)]
-    Validators { pagination: Option<Pagination> },
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for ValidatorAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
 }
 
+impl From<Addr> for ValidatorAddr {
+    fn from(addr: Addr) -> Self {
+        ValidatorAddr(addr.into_string())
+    }
+}
+
+impl From<String> for ValidatorAddr {
+    fn from(addr: String) -> Self {
+        ValidatorAddr(addr)
+    }
+}
+
+impl From<&str> for ValidatorAddr {
+    fn from(addr: &str) -> Self {
+        ValidatorAddr(addr.to_string())
+    }
+}
+
+// Unset fields are left out rather than sent as `null`; the SDK's pagination treats both as defaults
 #[cw_serde]
+#[derive(Default)]
 pub struct Pagination {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<Binary>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_opt_u64")]
     pub offset: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_opt_u64")]
     pub limit: Option<u64>,
+    #[serde(alias = "countTotal", skip_serializing_if = "Option::is_none")]
     pub count_total: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reverse: Option<bool>,
 }
 
+impl Pagination {
+    pub fn builder() -> PaginationBuilder {
+        PaginationBuilder::default()
+    }
+
+    /// Pagination that only sets a page size, which is the most common case
+    pub fn with_limit(limit: u64) -> Self {
+        Pagination { limit: Some(limit), ..Default::default() }
+    }
+}
+
+#[derive(Default)]
+pub struct PaginationBuilder {
+    pagination: Pagination,
+}
+
+impl PaginationBuilder {
+    pub fn key(mut self, key: Binary) -> Self {
+        self.pagination.key = Some(key);
+        self
+    }
+
+    pub fn offset(mut self, offset: u64) -> Self {
+        self.pagination.offset = Some(offset);
+        self
+    }
+
+    pub fn limit(mut self, limit: u64) -> Self {
+        self.pagination.limit = Some(limit);
+        self
+    }
+
+    pub fn count_total(mut self, count_total: bool) -> Self {
+        self.pagination.count_total = Some(count_total);
+        self
+    }
+
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.pagination.reverse = Some(reverse);
+        self
+    }
+
+    pub fn build(self) -> Pagination {
+        self.pagination
+    }
+}
+
 #[cw_serde]
 pub struct PaginationResponse {
+    #[serde(alias = "nextKey")]
     pub next_key: Option<Binary>,
+    #[serde(default, deserialize_with = "deserialize_opt_u64")]
     pub total: Option<u64>,
 }
 
+/// A duration in whole seconds. Serialized as a bare number of seconds, and like other `u64`s
+/// from the chain it also deserializes from a string.
+#[derive(Serialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, JsonSchema)]
+#[serde(transparent)]
+pub struct DurationSecs(u64);
+
+impl DurationSecs {
+    pub const fn from_secs(secs: u64) -> Self {
+        DurationSecs(secs)
+    }
+
+    /// `None` if the duration does not fit into a `u64` of seconds
+    pub const fn from_hours(hours: u64) -> Option<Self> {
+        match hours.checked_mul(3600) {
+            Some(secs) => Some(DurationSecs(secs)),
+            None => None,
+        }
+    }
+
+    /// `None` if the duration does not fit into a `u64` of seconds
+    pub const fn from_days(days: u64) -> Option<Self> {
+        match days.checked_mul(86_400) {
+            Some(secs) => Some(DurationSecs(secs)),
+            None => None,
+        }
+    }
+
+    pub const fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: DurationSecs) -> Option<DurationSecs> {
+        self.0.checked_add(other.0).map(DurationSecs)
+    }
+
+    pub fn checked_sub(self, other: DurationSecs) -> Option<DurationSecs> {
+        self.0.checked_sub(other.0).map(DurationSecs)
+    }
+
+    pub fn checked_mul(self, factor: u64) -> Option<DurationSecs> {
+        self.0.checked_mul(factor).map(DurationSecs)
+    }
+
+    pub fn saturating_add(self, other: DurationSecs) -> DurationSecs {
+        DurationSecs(self.0.saturating_add(other.0))
+    }
+
+    pub fn saturating_sub(self, other: DurationSecs) -> DurationSecs {
+        DurationSecs(self.0.saturating_sub(other.0))
+    }
+
+    /// `timestamp` plus this duration, failing if the result does not fit into a `Timestamp`
+    pub fn after(self, timestamp: Timestamp) -> StdResult<Timestamp> {
+        let nanos = Uint64::new(self.0).checked_mul(Uint64::new(1_000_000_000))?;
+        Ok(Timestamp::from_nanos(Uint64::new(timestamp.nanos()).checked_add(nanos)?.u64()))
+    }
+}
+
+impl<'de> Deserialize<'de> for DurationSecs {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_u64(deserializer).map(DurationSecs)
+    }
+}
+
+impl fmt::Display for DurationSecs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}s", self.0)
+    }
+}
+
+impl From<u64> for DurationSecs {
+    fn from(secs: u64) -> Self {
+        DurationSecs(secs)
+    }
+}
+
+impl From<DurationSecs> for u64 {
+    fn from(duration: DurationSecs) -> Self {
+        duration.0
+    }
+}
+
 #[cw_serde]
 pub struct AllianceParams {
-    pub reward_delay_time: u64,
-    pub take_rate_claim_interval: u64,
-    pub last_take_rate_claim_time: String,
+    pub reward_delay_time: DurationSecs,
+    pub take_rate_claim_interval: DurationSecs,
+    #[serde(serialize_with = "serialize_time_stamp", deserialize_with = "deserialize_time_stamp")]
+    pub last_take_rate_claim_time: Timestamp,
+}
+
+impl AllianceParams {
+    /// The earliest time at which the take rate can be claimed again
+    pub fn next_take_rate_claim_time(&self) -> StdResult<Timestamp> {
+        self.take_rate_claim_interval.after(self.last_take_rate_claim_time)
+    }
+
+    pub fn is_take_rate_claimable(&self, now: Timestamp) -> bool {
+        self.next_take_rate_claim_time()
+            .map(|next| now >= next)
+            .unwrap_or(false)
+    }
+
+    /// When rewards of an alliance starting at `reward_start` unlock, i.e. `reward_start` plus
+    /// `reward_delay_time`. Saturates at the largest representable `Timestamp`.
+    pub fn reward_unlock_time(&self, reward_start: Timestamp) -> Timestamp {
+        self.reward_delay_time
+            .after(reward_start)
+            .unwrap_or_else(|_| Timestamp::from_nanos(u64::MAX))
+    }
+
+    pub fn is_reward_unlocked(&self, reward_start: Timestamp, now: Timestamp) -> bool {
+        now >= self.reward_unlock_time(reward_start)
+    }
 }
 
 #[cw_serde]
 pub struct DecCoin {
     // TODO: why is this an option
     pub denom: Option<String>,
+    #[serde(deserialize_with = "deserialize_decimal_or_zero")]
     pub amount: Decimal256,
 }
 
+impl DecCoin {
+    /// The denom, or `""` when the chain omitted it
+    pub fn denom_str(&self) -> &str {
+        self.denom.as_deref().unwrap_or_default()
+    }
+
+    pub fn require_denom(&self) -> Result<&str, AllianceError> {
+        self.denom.as_deref().ok_or(AllianceError::MissingDenom {})
+    }
+
+    /// Converts to a whole-token `Coin`, rounding the amount down
+    pub fn to_coin_floor(&self) -> Result<Coin, AllianceError> {
+        self.to_coin(self.amount.to_uint_floor())
+    }
+
+    /// Converts to a whole-token `Coin`, rounding the amount up
+    pub fn to_coin_ceil(&self) -> Result<Coin, AllianceError> {
+        self.to_coin(self.amount.to_uint_ceil())
+    }
+
+    fn to_coin(&self, amount: Uint256) -> Result<Coin, AllianceError> {
+        let denom = self.require_denom()?;
+        let amount = Uint128::try_from(amount)
+            .map_err(|_| AllianceError::DecimalOverflow { value: self.amount.to_string() })?;
+        Ok(Coin::new(amount.u128(), denom))
+    }
+}
+
+/// Sums the amounts of all coins with the given denom, saturating at `Decimal256::MAX`
+pub fn sum_dec_coins(coins: &[DecCoin], denom: &str) -> Decimal256 {
+    coins
+        .iter()
+        .filter(|c| c.denom.as_deref() == Some(denom))
+        .fold(Decimal256::zero(), |acc, c| acc.saturating_add(c.amount))
+}
+
+impl From<Coin> for DecCoin {
+    fn from(coin: Coin) -> Self {
+        DecCoin { denom: Some(coin.denom), amount: Decimal256::from_ratio(coin.amount, 1u128) }
+    }
+}
+
+/// Fails with `NonIntegerAmount` if the amount has a fractional part; use `DecCoin::to_coin_floor`
+/// or `DecCoin::to_coin_ceil` to round explicitly
+impl TryFrom<DecCoin> for Coin {
+    type Error = AllianceError;
+
+    fn try_from(coin: DecCoin) -> Result<Self, AllianceError> {
+        if coin.amount.floor() != coin.amount {
+            return Err(AllianceError::NonIntegerAmount { amount: coin.amount.to_string() });
+        }
+        coin.to_coin_floor()
+    }
+}
+
+/// Whether `a` and `b` differ by at most `epsilon`
+pub fn dec_approx_eq(a: Decimal256, b: Decimal256, epsilon: Decimal256) -> bool {
+    a.abs_diff(b) <= epsilon
+}
+
+/// Collapses coins into a total per denom, saturating at `Decimal256::MAX`. Coins without a
+/// denom are skipped.
+pub fn group_dec_coins(coins: &[DecCoin]) -> BTreeMap<String, Decimal256> {
+    let mut totals = BTreeMap::new();
+    for coin in coins {
+        if let Some(denom) = &coin.denom {
+            let total = totals.entry(denom.clone()).or_insert_with(Decimal256::zero);
+            *total = total.saturating_add(coin.amount);
+        }
+    }
+    totals
+}
+
 #[cw_serde]
 pub struct ValidatorResponse {
     pub validator_addr: Addr,
+    #[serde(default)]
     pub total_delegation_shares: Vec<DecCoin>,
+    #[serde(default)]
     pub validator_shares: Vec<DecCoin>,
+    #[serde(default)]
     pub total_staked: Vec<DecCoin>,
 }
 
+impl ValidatorResponse {
+    pub fn total_staked_of(&self, denom: &str) -> Decimal256 {
+        sum_dec_coins(&self.total_staked, denom)
+    }
+
+    pub fn validator_shares_of(&self, denom: &str) -> Decimal256 {
+        sum_dec_coins(&self.validator_shares, denom)
+    }
+
+    pub fn total_delegation_shares_of(&self, denom: &str) -> Decimal256 {
+        sum_dec_coins(&self.total_delegation_shares, denom)
+    }
+
+    /// Tokens per validator share for `denom`
+    pub fn exchange_rate(&self, denom: &str) -> Result<Decimal256, AllianceError> {
+        let validator_shares = self.validator_shares_of(denom);
+        if validator_shares.is_zero() {
+            return Err(AllianceError::ZeroShares { denom: denom.to_string() });
+        }
+        Ok(self.total_staked_of(denom).checked_div(validator_shares)?)
+    }
+
+    /// Converts validator shares of `denom` into the underlying token amount
+    pub fn shares_to_tokens(&self, denom: &str, shares: Decimal256) -> Result<Decimal256, AllianceError> {
+        let validator_shares = self.validator_shares_of(denom);
+        if validator_shares.is_zero() {
+            return Err(AllianceError::ZeroShares { denom: denom.to_string() });
+        }
+        Ok(shares.checked_mul(self.total_staked_of(denom))?.checked_div(validator_shares)?)
+    }
+}
+
 #[cw_serde]
 pub struct ValidatorsResponse {
+    #[serde(default)]
     pub validators: Vec<ValidatorResponse>,
     pub pagination: Option<PaginationResponse>,
 }
 
+impl ValidatorsResponse {
+    /// The validators keyed by address. Should an address appear more than once, the first one is kept.
+    pub fn by_address(&self) -> BTreeMap<&Addr, &ValidatorResponse> {
+        let mut index = BTreeMap::new();
+        for validator in &self.validators {
+            index.entry(&validator.validator_addr).or_insert(validator);
+        }
+        index
+    }
+}
+
 #[cw_serde]
 pub struct ParamsResponse {
     pub params: AllianceParams,
@@ -123,28 +451,208 @@ pub struct ParamsResponse {
 
 #[cw_serde]
 pub struct WeightRange {
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub min: Decimal256,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub max: Decimal256,
 }
 
+impl WeightRange {
+    pub fn new(min: Decimal256, max: Decimal256) -> Result<WeightRange, AllianceError> {
+        if min > max {
+            return Err(AllianceError::InvalidWeightRange { min: min.to_string(), max: max.to_string() });
+        }
+        Ok(WeightRange { min, max })
+    }
+
+    /// Whether `w` lies within the range, bounds included
+    pub fn contains(&self, w: Decimal256) -> bool {
+        self.min <= w && w <= self.max
+    }
+
+    pub fn clamp(&self, w: Decimal256) -> Decimal256 {
+        w.max(self.min).min(self.max)
+    }
+}
+
 #[cw_serde]
 pub struct AllianceAsset {
     pub denom: String,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub reward_weight: Decimal256,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub consensus_weight: Decimal256,
-    pub consensus_cap: Decimal256,
+    // Only emitted by newer versions of the alliance module
+    #[serde(default, deserialize_with = "deserialize_opt_decimal")]
+    pub consensus_cap: Option<Decimal256>,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub take_rate: Decimal256,
+    #[serde(deserialize_with = "deserialize_decimal_or_zero")]
     pub total_tokens: Decimal256,
+    #[serde(deserialize_with = "deserialize_decimal_or_zero")]
     pub total_validator_shares: Decimal256,
     #[serde(serialize_with = "serialize_time_stamp", deserialize_with = "deserialize_time_stamp")]
     pub reward_start_time: Timestamp, // "2023-06-06T18:37:29.956787974Z"
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub reward_change_rate: Decimal256,
-    pub reward_change_interval: u64,
-    pub last_reward_change_time: String,
+    pub reward_change_interval: DurationSecs,
+    #[serde(serialize_with = "serialize_time_stamp", deserialize_with = "deserialize_time_stamp")]
+    pub last_reward_change_time: Timestamp,
     pub reward_weight_range: WeightRange,
     pub is_initialized: Option<bool>,
 }
 
+impl AllianceAsset {
+    /// Whether the asset has been initialized. `is_initialized` stays optional on the wire because
+    /// older chain versions omit it; a missing value is treated as not initialized.
+    pub fn initialized(&self) -> bool {
+        self.is_initialized.unwrap_or(false)
+    }
+
+    pub fn reward_weight_in_range(&self) -> bool {
+        self.reward_weight_range.contains(self.reward_weight)
+    }
+
+    /// `total_tokens` after the take rate has been applied `periods` times, i.e.
+    /// `total_tokens * (1 - take_rate)^periods`. Saturates at zero.
+    pub fn tokens_after_take_rate(&self, periods: u64) -> Decimal256 {
+        let retained = Decimal256::one().saturating_sub(self.take_rate);
+        if retained == Decimal256::one() {
+            return self.total_tokens;
+        }
+        // Square-and-multiply; every factor is at most one so this can only shrink towards zero
+        let mut tokens = self.total_tokens;
+        let mut base = retained;
+        let mut exp = periods;
+        while exp > 0 && !tokens.is_zero() {
+            if exp & 1 == 1 {
+                tokens *= base;
+            }
+            base *= base;
+            exp >>= 1;
+        }
+        tokens
+    }
+
+    /// The asset's contribution to consensus power, `total_tokens * consensus_weight`.
+    /// Saturates at `Decimal256::MAX`.
+    pub fn consensus_power(&self) -> Decimal256 {
+        self.total_tokens.saturating_mul(self.consensus_weight)
+    }
+
+    /// A rough APR estimate of `reward_weight * inflation / total_bonded`. This assumes the
+    /// asset's share of rewards is its reward weight applied to the chain's inflation, and ignores
+    /// the take rate, reward weight changes, commission and compounding. Returns zero when
+    /// `total_bonded` is zero, and saturates at `Decimal256::MAX`.
+    pub fn estimated_apr(&self, total_bonded: Decimal256, inflation: Decimal256) -> Decimal256 {
+        if total_bonded.is_zero() {
+            return Decimal256::zero();
+        }
+        match self.reward_weight.checked_mul(inflation) {
+            Ok(r) => r.checked_div(total_bonded).unwrap_or(Decimal256::MAX),
+            // Divide first when the product overflows, trading some precision for range
+            Err(_) => self.reward_weight.checked_div(total_bonded).map_or(Decimal256::MAX, |r| r.saturating_mul(inflation)),
+        }
+    }
+
+    /// The reward weight after `intervals` more reward change intervals, clamped to
+    /// `reward_weight_range`. Each interval multiplies the weight by `reward_change_rate`,
+    /// so a rate below one decreases the weight over time and a rate above one increases it.
+    pub fn projected_reward_weight(&self, intervals: u64) -> Decimal256 {
+        let range = &self.reward_weight_range;
+        let mut weight = self.reward_weight;
+        let mut factor = self.reward_change_rate;
+        let mut exp = intervals;
+        while exp > 0 && !weight.is_zero() {
+            if exp & 1 == 1 {
+                match weight.checked_mul(factor) {
+                    Ok(w) => weight = w,
+                    Err(_) => return range.max,
+                }
+            }
+            exp >>= 1;
+            if exp > 0 {
+                // Only a factor above one can overflow, and multiplying by it again would too
+                match factor.checked_mul(factor) {
+                    Ok(f) => factor = f,
+                    Err(_) => return range.max,
+                }
+            }
+        }
+        range.clamp(weight)
+    }
+
+    pub fn rewards_started(&self, now: Timestamp) -> bool {
+        now >= self.reward_start_time
+    }
+
+    /// Seconds until rewards start accruing, rounded up, or 0 once they have started
+    pub fn time_until_rewards(&self, now: Timestamp) -> u64 {
+        let remaining = self.reward_start_time.nanos().saturating_sub(now.nanos());
+        remaining.div_ceil(1_000_000_000)
+    }
+}
+
+/// Whether `denom` is an IBC voucher denom, i.e. `ibc/` followed by a 64 character
+/// uppercase hex hash of the denom trace
+pub fn is_ibc_denom(denom: &str) -> bool {
+    match denom.strip_prefix("ibc/") {
+        Some(hash) => hash.len() == 64 && hash.bytes().all(|c| matches!(c, b'0'..=b'9' | b'A'..=b'F')),
+        None => false,
+    }
+}
+
+pub fn validate_ibc_denom(denom: &str) -> Result<(), AllianceError> {
+    if !is_ibc_denom(denom) {
+        return Err(AllianceError::InvalidDenom { denom: denom.to_string() });
+    }
+    Ok(())
+}
+
+/// Sorts alliances by denom. `AllianceAsset` does not implement `Ord` since its equality
+/// covers every field, not just the denom.
+pub fn sort_alliances_by_denom(alliances: &mut [AllianceAsset]) {
+    alliances.sort_by(|a, b| a.denom.cmp(&b.denom));
+}
+
+/// Keeps only the alliances that are initialized, see `AllianceAsset::initialized`
+pub fn filter_initialized(alliances: Vec<AllianceAsset>) -> Vec<AllianceAsset> {
+    alliances.into_iter().filter(AllianceAsset::initialized).collect()
+}
+
+pub fn initialized_alliances(alliances: &[AllianceAsset]) -> Vec<&AllianceAsset> {
+    alliances.iter().filter(|a| a.initialized()).collect()
+}
+
+// With the `rfc3339-time` feature (the default) timestamps use the chain's RFC3339 format,
+// e.g. "2023-06-06T18:37:29.956787974Z". Without it they are a plain number of nanoseconds
+// since the epoch.
+#[cfg(feature = "rfc3339-time")]
+fn serialize_time_stamp<S>(
+    time_stamp: &Timestamp,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&rfc3339::format(time_stamp.nanos()))
+}
+
+#[cfg(feature = "rfc3339-time")]
+fn deserialize_time_stamp<'de, D>(
+    deserializer: D,
+) -> Result<Timestamp, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let date = String::deserialize(deserializer)?;
+    let nanos = rfc3339::parse(&date).map_err(de::Error::custom)?;
+    let nanos = u64::try_from(nanos)
+        .map_err(|_| de::Error::custom(AllianceError::TimestampOutOfRange { timestamp: date }))?;
+    Ok(Timestamp::from_nanos(nanos))
+}
+
+#[cfg(not(feature = "rfc3339-time"))]
 fn serialize_time_stamp<S>(
     time_stamp: &Timestamp,
     serializer: S,
@@ -152,18 +660,245 @@ fn serialize_time_stamp<S>(
 where
     S: Serializer,
 {
-    let date: DateTime<Utc> = Utc.timestamp_nanos(time_stamp.nanos() as i64);
-    date.serialize(serializer)
+    serializer.serialize_u64(time_stamp.nanos())
 }
 
+// Also accepts the quoted nanoseconds of cosmwasm's native `Timestamp` encoding
+#[cfg(not(feature = "rfc3339-time"))]
 fn deserialize_time_stamp<'de, D>(
     deserializer: D,
 ) -> Result<Timestamp, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let date = DateTime::<Utc>::deserialize(deserializer)?;
-    Ok(Timestamp::from_nanos(date.timestamp_nanos() as u64))
+    deserialize_u64(deserializer).map(Timestamp::from_nanos)
+}
+
+/// gRPC-gateway JSON encodes `uint64` as a string, so accept both forms
+struct LenientU64(u64);
+
+impl<'de> Deserialize<'de> for LenientU64 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(LenientU64Visitor).map(LenientU64)
+    }
+}
+
+struct LenientU64Visitor;
+
+impl<'de> de::Visitor<'de> for LenientU64Visitor {
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a u64 as a number or a string")
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        v.parse().map_err(E::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let number = number_token(map, &self)?;
+        self.visit_str(&number)
+    }
+}
+
+// serde_json's `arbitrary_precision` feature hands numbers to `deserialize_any` as a map with this
+// single key and the number's source text as the value, so fractions arrive without f64 rounding
+const SERDE_JSON_NUMBER_TOKEN: &str = "$serde_json::private::Number";
+
+fn number_token<'de, A: de::MapAccess<'de>>(mut map: A, expected: &dyn de::Expected) -> Result<String, A::Error> {
+    match map.next_key::<String>()? {
+        Some(key) if key == SERDE_JSON_NUMBER_TOKEN => map.next_value(),
+        _ => Err(de::Error::invalid_type(de::Unexpected::Map, expected)),
+    }
+}
+
+fn deserialize_u64<'de, D>(
+    deserializer: D,
+) -> Result<u64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    LenientU64::deserialize(deserializer).map(|n| n.0)
+}
+
+fn deserialize_opt_u64<'de, D>(
+    deserializer: D,
+) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<LenientU64>::deserialize(deserializer).map(|n| n.map(|n| n.0))
+}
+
+/// Gateways disagree on whether decimals are quoted, so accept both `"1.5"` and unquoted numbers
+/// such as `15` or `1.5`. Unquoted fractions are read from the number's source text, never through
+/// an `f64`, which needs a deserializer that exposes it: serde_json with `arbitrary_precision`
+/// does, while `cosmwasm_std::from_json` rejects unquoted fractions before they get here.
+struct LenientDecimal(Decimal256);
+
+impl<'de> Deserialize<'de> for LenientDecimal {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(LenientDecimalVisitor).map(LenientDecimal)
+    }
+}
+
+struct LenientDecimalVisitor;
+
+impl<'de> de::Visitor<'de> for LenientDecimalVisitor {
+    type Value = Decimal256;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal as a string or a number")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        #[cfg(feature = "lenient-decimals")]
+        if v.contains(['e', 'E']) {
+            let fixed = scientific_to_fixed(v).ok_or_else(|| E::custom(format!("invalid decimal: {}", v)))?;
+            return Decimal256::from_str(&fixed).map_err(E::custom);
+        }
+        Decimal256::from_str(v).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        self.visit_str(&v.to_string())
+    }
+
+    // Exponents such as `1e-18` take the same path as a quoted `"1e-18"`
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+        let number = number_token(map, &self)?;
+        self.visit_str(&number)
+    }
+}
+
+/// Rewrites scientific notation such as `1.5e2` as fixed-point (`150`). Returns `None` for
+/// malformed input, and for exponents far outside what a `Decimal256` can hold.
+#[cfg(feature = "lenient-decimals")]
+fn scientific_to_fixed(s: &str) -> Option<String> {
+    let (mantissa, exponent) = s.split_once(['e', 'E'])?;
+    let exponent: i64 = exponent.parse().ok()?;
+    if exponent.abs() > 100 {
+        return None;
+    }
+    let (int_part, frac_part) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    if int_part.is_empty() && frac_part.is_empty() || !int_part.bytes().chain(frac_part.bytes()).all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let digits = format!("{}{}", int_part, frac_part);
+    let point = int_part.len() as i64 + exponent;
+    let fixed = if point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else if point as usize >= digits.len() {
+        format!("{}{}", digits, "0".repeat(point as usize - digits.len()))
+    } else {
+        format!("{}.{}", &digits[..point as usize], &digits[point as usize..])
+    };
+    // Trailing zeros after the point would count against Decimal256's 18 fractional digits
+    Some(if fixed.contains('.') { fixed.trim_end_matches('0').trim_end_matches('.').to_string() } else { fixed })
+}
+
+fn deserialize_decimal<'de, D>(
+    deserializer: D,
+) -> Result<Decimal256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    LenientDecimal::deserialize(deserializer).map(|d| d.0)
+}
+
+// With the `lenient-decimals` feature, share and amount fields read a JSON `null` as zero
+// instead of failing, since partial chain data sometimes omits them.
+#[cfg(feature = "lenient-decimals")]
+fn deserialize_decimal_or_zero<'de, D>(
+    deserializer: D,
+) -> Result<Decimal256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<LenientDecimal>::deserialize(deserializer).map(|d| d.map_or(Decimal256::zero(), |d| d.0))
+}
+
+#[cfg(not(feature = "lenient-decimals"))]
+fn deserialize_decimal_or_zero<'de, D>(
+    deserializer: D,
+) -> Result<Decimal256, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_decimal(deserializer)
+}
+
+fn deserialize_opt_decimal<'de, D>(
+    deserializer: D,
+) -> Result<Option<Decimal256>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<LenientDecimal>::deserialize(deserializer).map(|d| d.map(|d| d.0))
+}
+
+/// Some gateways encode the reward history as a `{ denom: index }` object instead of a list.
+/// Like `LenientDecimal`, this uses a hand-written visitor rather than `#[serde(untagged)]`,
+/// whose buffering would pull float code into contract wasm.
+struct RewardHistory(Vec<Option<Reward>>);
+
+impl<'de> Deserialize<'de> for RewardHistory {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(RewardHistoryVisitor).map(RewardHistory)
+    }
+}
+
+struct RewardHistoryVisitor;
+
+impl<'de> de::Visitor<'de> for RewardHistoryVisitor {
+    type Value = Vec<Option<Reward>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of rewards or a map of denoms to reward indices")
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut rewards = vec![];
+        while let Some(reward) = seq.next_element()? {
+            rewards.push(reward);
+        }
+        Ok(rewards)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut indices = BTreeMap::new();
+        while let Some((denom, index)) = map.next_entry::<String, LenientDecimal>()? {
+            indices.insert(denom, index.0);
+        }
+        Ok(indices.into_iter().map(|(denom, index)| Some(Reward { denom: Some(denom), index })).collect())
+    }
+}
+
+fn deserialize_reward_history<'de, D>(
+    deserializer: D,
+) -> Result<Option<Vec<Option<Reward>>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<RewardHistory>::deserialize(deserializer).map(|history| history.map(|h| h.0))
 }
 
 #[cw_serde]
@@ -171,224 +906,1121 @@ pub struct AllianceResponse {
     pub alliance: AllianceAsset,
 }
 
+impl From<AllianceResponse> for AllianceAsset {
+    fn from(res: AllianceResponse) -> Self {
+        res.alliance
+    }
+}
+
 #[cw_serde]
 pub struct AlliancesResponse {
+    #[serde(default)]
     pub alliances: Vec<AllianceAsset>,
     pub pagination: Option<PaginationResponse>,
 }
 
+impl AlliancesResponse {
+    /// The alliances keyed by denom. Should a denom appear more than once, the first one is kept,
+    /// matching `find`.
+    pub fn index_by_denom(&self) -> BTreeMap<&str, &AllianceAsset> {
+        let mut index = BTreeMap::new();
+        for alliance in &self.alliances {
+            index.entry(alliance.denom.as_str()).or_insert(alliance);
+        }
+        index
+    }
+
+    pub fn find(&self, denom: &str) -> Option<&AllianceAsset> {
+        self.alliances.iter().find(|a| a.denom == denom)
+    }
+
+    /// Denoms of the alliances that are not initialized yet
+    pub fn uninitialized_denoms(&self) -> Vec<String> {
+        self.alliances.iter().filter(|a| !a.initialized()).map(|a| a.denom.clone()).collect()
+    }
+
+    /// The summed `consensus_power` of all alliances, saturating at `Decimal256::MAX`
+    pub fn total_consensus_power(&self) -> Decimal256 {
+        self.alliances.iter().fold(Decimal256::zero(), |acc, a| acc.saturating_add(a.consensus_power()))
+    }
+
+    /// `total_tokens` per denom. The chain keeps one alliance per denom, but should a denom
+    /// appear more than once its amounts are summed, saturating at `Decimal256::MAX`.
+    pub fn total_tokens_by_denom(&self) -> BTreeMap<String, Decimal256> {
+        let mut totals = BTreeMap::new();
+        for alliance in &self.alliances {
+            let total = totals.entry(alliance.denom.clone()).or_insert_with(Decimal256::zero);
+            *total = total.saturating_add(alliance.total_tokens);
+        }
+        totals
+    }
+}
+
+#[cw_serde]
+pub struct RewardRateResponse {
+    pub denom: String,
+    #[serde(deserialize_with = "deserialize_decimal")]
+    pub rate: Decimal256,
+}
+
+#[cw_serde]
+pub struct UnbondingDelegationsResponse {
+    #[serde(default)]
+    pub unbondings: Vec<UnbondingDelegation>,
+    pub pagination: Option<PaginationResponse>,
+}
+
+#[cw_serde]
+pub struct UnbondingDelegation {
+    pub validator_addr: ValidatorAddr,
+    pub denom: String,
+    pub amount: Uint128,
+    #[serde(serialize_with = "serialize_time_stamp", deserialize_with = "deserialize_time_stamp")]
+    pub completion_time: Timestamp,
+}
+
+#[cw_serde]
+pub struct RewardPoolResponse {
+    pub denom: String,
+    #[serde(deserialize_with = "deserialize_decimal")]
+    pub balance: Decimal256,
+}
+
 #[cw_serde]
 pub struct AlliancesDelegationsResponse {
     pub delegations: Option<Vec<SingleDelegationResponse>>,
     pub pagination: Option<PaginationResponse>,
 }
 
+impl AlliancesDelegationsResponse {
+    /// The delegations, or an empty slice when the chain returned none.
+    /// The wire type stays optional since the chain sends `null` rather than `[]` when empty.
+    pub fn delegations_or_empty(&self) -> &[SingleDelegationResponse] {
+        self.delegations.as_deref().unwrap_or_default()
+    }
+
+    /// The delegated balances summed per denom, saturating at `Uint128::MAX`
+    pub fn total_balance_by_denom(&self) -> BTreeMap<String, Uint128> {
+        group_coins(self.delegations_or_empty().iter().map(|d| &d.balance))
+    }
+}
+
+/// All of a delegator's alliance delegations, with their balances summed per denom
+#[cfg(feature = "queries")]
+#[cw_serde]
+pub struct DelegatorPortfolio {
+    pub delegator_addr: Addr,
+    pub delegations: Vec<SingleDelegationResponse>,
+    pub balances: BTreeMap<String, Uint128>,
+}
+
+#[cfg(feature = "queries")]
+impl DelegatorPortfolio {
+    pub fn new(delegator_addr: Addr, delegations: Vec<SingleDelegationResponse>) -> Self {
+        let balances = group_coins(delegations.iter().map(|d| &d.balance));
+        DelegatorPortfolio { delegator_addr, delegations, balances }
+    }
+}
+
 #[cw_serde]
 pub struct DelegationRewardsResponse {
+    #[serde(default)]
     pub rewards: Vec<Coin>,
 }
 
+impl DelegationRewardsResponse {
+    pub fn total_by_denom(&self) -> BTreeMap<String, Uint128> {
+        sum_rewards(std::slice::from_ref(self))
+    }
+}
+
+/// Merges the rewards of many responses into a total per denom, saturating at `Uint128::MAX`
+pub fn sum_rewards(responses: &[DelegationRewardsResponse]) -> BTreeMap<String, Uint128> {
+    group_coins(responses.iter().flat_map(|r| &r.rewards))
+}
+
+/// Collapses coins into a total per denom, saturating at `Uint128::MAX`
+fn group_coins<'a>(coins: impl IntoIterator<Item = &'a Coin>) -> BTreeMap<String, Uint128> {
+    let mut totals = BTreeMap::new();
+    for coin in coins {
+        let total = totals.entry(coin.denom.clone()).or_insert_with(Uint128::zero);
+        *total = total.saturating_add(coin.amount);
+    }
+    totals
+}
+
 #[cw_serde]
 pub struct DelegationResponse {
     pub delegation: SingleDelegationResponse,
 }
 
+// The field names mirror the chain's JSON, which nests `delegation` twice. These accessors
+// spare callers from writing `res.delegation.delegation`.
+impl DelegationResponse {
+    pub fn inner_delegation(&self) -> &Delegation {
+        &self.delegation.delegation
+    }
+
+    pub fn delegation_balance(&self) -> &Coin {
+        &self.delegation.balance
+    }
+}
+
 #[cw_serde]
 pub struct SingleDelegationResponse {
     pub delegation: Delegation,
     pub balance: Coin,
 }
 
+impl SingleDelegationResponse {
+    pub fn shares(&self) -> Decimal256 {
+        self.delegation.shares
+    }
+
+    pub fn balance(&self) -> &Coin {
+        &self.balance
+    }
+}
+
 #[cw_serde]
 pub struct Delegation {
+    #[serde(alias = "delegatorAddress")]
     pub delegator_address: Option<Addr>,
+    #[serde(alias = "validatorAddress")]
     pub validator_address: Option<Addr>,
     pub denom: Option<String>,
+    #[serde(deserialize_with = "deserialize_decimal_or_zero")]
     pub shares: Decimal256,
+    #[serde(default, alias = "rewardHistory", deserialize_with = "deserialize_reward_history")]
     pub reward_history: Option<Vec<Option<Reward>>>,
+    #[serde(alias = "lastRewardClaimHeight")]
     pub last_reward_claim_height: Option<u64>,
 }
 
+// Should a denom appear more than once in `reward_history`, the highest index counts, since reward
+// indices only grow. `reward_index_map`, `reward_index`, `pending_reward` and
+// `dedup_reward_history` all follow this rule.
+impl Delegation {
+    /// The rewards in `reward_history`, skipping empty slots
+    pub fn rewards_iter(&self) -> impl Iterator<Item = &Reward> {
+        self.reward_history.iter().flatten().flatten()
+    }
+
+    /// The reward history keyed by denom, keeping the highest index of a repeated denom.
+    /// Rewards without a denom are skipped.
+    pub fn reward_index_map(&self) -> BTreeMap<String, Decimal256> {
+        let mut indices = BTreeMap::new();
+        for reward in self.rewards_iter() {
+            if let Some(denom) = &reward.denom {
+                let index = indices.entry(denom.clone()).or_insert(reward.index);
+                *index = (*index).max(reward.index);
+            }
+        }
+        indices
+    }
+
+    /// The reward index recorded for `denom` at the last claim, the highest one if `denom` repeats
+    pub fn reward_index(&self, denom: &str) -> Option<Decimal256> {
+        self.rewards_iter()
+            .filter(|r| r.denom.as_deref() == Some(denom))
+            .map(|r| r.index)
+            .max()
+    }
+
+    /// Drops empty slots from `reward_history` and collapses entries sharing a denom into one,
+    /// keeping the highest index. Entries keep the position of their denom's first occurrence;
+    /// rewards without a denom are left as they are.
+    pub fn dedup_reward_history(&mut self) {
+        let Some(history) = self.reward_history.take() else {
+            return;
+        };
+        let mut deduped: Vec<Option<Reward>> = Vec::with_capacity(history.len());
+        for reward in history.into_iter().flatten() {
+            let existing = reward.denom.as_ref().and_then(|denom| {
+                deduped.iter_mut().flatten().find(|r| r.denom.as_ref() == Some(denom))
+            });
+            match existing {
+                Some(existing) => existing.index = existing.index.max(reward.index),
+                None => deduped.push(Some(reward)),
+            }
+        }
+        self.reward_history = Some(deduped);
+    }
+
+    /// Rewards accrued since the last claim, given the validator's current index for `denom`.
+    /// A missing history entry counts as an index of zero, and a current index below the
+    /// stored one yields zero. Saturates at `Decimal256::MAX`.
+    pub fn pending_reward(&self, denom: &str, current_index: Decimal256) -> Decimal256 {
+        let stored_index = self.reward_index(denom).unwrap_or_default();
+        current_index.saturating_sub(stored_index).saturating_mul(self.shares)
+    }
+}
+
 #[cw_serde]
 pub struct Reward {
     pub denom: Option<String>,
+    #[serde(deserialize_with = "deserialize_decimal")]
     pub index: Decimal256,
 }
 
-pub trait CreateAllianceMsg: From<AllianceMsg> {
-    fn alliance_delegate(
-        delegator_address: Addr,
-        validator_address: Addr,
-        amount: Coin,
-    ) -> Self {
-        AllianceMsg::Delegate { delegator_address, validator_address, amount }.into()
+impl Reward {
+    /// The denom, or `""` when the chain omitted it
+    pub fn denom_str(&self) -> &str {
+        self.denom.as_deref().unwrap_or_default()
     }
 
-    fn alliance_undelegate(
-        delegator_address: Addr,
-        validator_address: Addr,
-        amount: Coin,
-    ) -> Self {
-        AllianceMsg::Undelegate { delegator_address, validator_address, amount }.into()
+    pub fn require_denom(&self) -> Result<&str, AllianceError> {
+        self.denom.as_deref().ok_or(AllianceError::MissingDenom {})
     }
+}
 
-    fn alliance_redelegate(
-        delegator_address: Addr,
-        validator_src_address: Addr,
-        validator_dst_address: Addr,
-        amount: Coin,
-    ) -> Self {
-        AllianceMsg::Redelegate { delegator_address, validator_src_address, validator_dst_address, amount }.into()
-    }
+// This export is added to all contracts that import this package, signifying that they require
+// "alliance" support on the chain they run on.
+#[no_mangle]
+extern "C" fn requires_alliance() {}
+
+// Referencing the function keeps its Rust name and signature fixed at compile time. The exported
+// symbol name comes from `#[no_mangle]`, which is covered by `requires_alliance_is_exported` below.
+const _: extern "C" fn() = requires_alliance;
 
-    fn alliance_claim_deligation_rewards(
-        delegator_address: Addr,
-        validator_address: Addr,
-        denom: String,
-    ) -> Self {
-        AllianceMsg::ClaimDelegationRewards { delegator_address, validator_address, denom }.into()
+// The chain checks for the exported `requires_alliance` symbol by name. Declaring it here resolves
+// it by that name at link time, so the tests fail to link if the attribute or the name changes.
+#[cfg(test)]
+mod requires_alliance_symbol {
+    extern "C" {
+        #[link_name = "requires_alliance"]
+        fn exported_requires_alliance();
     }
 
+    #[test]
+    fn requires_alliance_is_exported() {
+        unsafe { exported_requires_alliance() }
+    }
 }
 
-impl<T> CreateAllianceMsg for T where T: From<AllianceMsg> {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::*;
+    use cosmwasm_std::{from_json, to_json_string};
+    use serde::de::DeserializeOwned;
 
-pub trait AllianceQuerier {
-    fn query_alliance_alliance(
-        &self,
-        denom: String,
-    ) -> StdResult<AllianceResponse>;
+    fn round_trip<T: Serialize + DeserializeOwned + PartialEq + fmt::Debug>(value: &T) {
+        let json = to_json_string(value).unwrap();
+        assert_eq!(&from_json::<T>(json.as_bytes()).unwrap(), value, "{}", json);
+    }
 
-    fn query_alliance_alliances(
-        &self,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesResponse>;
+    #[test]
+    fn response_types_round_trip() {
+        round_trip(&alliance_asset("ibc/alliance"));
+        round_trip(&AllianceAsset { consensus_cap: None, is_initialized: None, ..alliance_asset("ibc/alliance") });
+        round_trip(&validator("cosmosvaloper1validator"));
+        round_trip(&DelegationResponse { delegation: delegation("cosmos1delegator", "cosmosvaloper1validator", "uluna", 1000) });
+        round_trip(&DelegationRewardsResponse { rewards: vec![Coin::new(10, "uluna"), Coin::new(5, "uatom")] });
+        round_trip(&ParamsResponse {
+            params: AllianceParams {
+                reward_delay_time: DurationSecs::from_secs(86_400),
+                take_rate_claim_interval: DurationSecs::from_secs(300),
+                last_take_rate_claim_time: Timestamp::from_nanos(1_686_076_649_956_787_974),
+            },
+        });
+    }
 
-    fn query_alliance_alliances_delegations(
-        &self,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesDelegationsResponse>;
+    #[test]
+    fn consensus_cap_is_optional() {
+        let with_cap = to_json_string(&alliance_asset("uluna")).unwrap();
+        assert!(with_cap.contains(r#""consensus_cap":"0.3","#), "{}", with_cap);
+        round_trip(&alliance_asset("uluna"));
+        let without_cap = AllianceAsset { consensus_cap: None, ..alliance_asset("uluna") };
+        round_trip(&without_cap);
+        // Chains that predate the field leave it out entirely
+        let json = with_cap.replace(r#""consensus_cap":"0.3","#, "");
+        assert_eq!(from_json::<AllianceAsset>(json.as_bytes()).unwrap(), without_cap);
+    }
 
-    fn query_alliance_alliances_delegation_by_validator(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesDelegationsResponse>;
+    // How the fixtures' 2023-06-06T18:37:29.956787974Z timestamp appears on the wire
+    #[cfg(feature = "rfc3339-time")]
+    const EXAMPLE_TIME_JSON: &str = r#""2023-06-06T18:37:29.956787974Z""#;
+    #[cfg(not(feature = "rfc3339-time"))]
+    const EXAMPLE_TIME_JSON: &str = "1686076649956787974";
 
-    fn query_alliance_delegation(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    ) -> StdResult<DelegationResponse>;
+    // Responses in the shape the chain returns them, with `uint64`s quoted by the gateway
+    #[cfg(feature = "rfc3339-time")]
+    const ALLIANCES_FIXTURE: &str = include_str!("../testdata/alliances.json");
+    const VALIDATOR_FIXTURE: &str = include_str!("../testdata/validator.json");
+    #[cfg(feature = "rfc3339-time")]
+    const PARAMS_FIXTURE: &str = include_str!("../testdata/params.json");
 
-    fn query_alliance_delegation_rewards(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    ) -> StdResult<DelegationRewardsResponse>;
+    #[cfg(feature = "rfc3339-time")]
+    #[test]
+    fn chain_params_response_round_trips() {
+        let res: ParamsResponse = from_json(PARAMS_FIXTURE.as_bytes()).unwrap();
+        assert_eq!(res.params.reward_delay_time, DurationSecs::from_secs(86_400));
+        assert_eq!(res.params.take_rate_claim_interval, DurationSecs::from_secs(300));
+        assert_eq!(res.params.last_take_rate_claim_time, Timestamp::from_nanos(1_686_076_649_956_787_974));
+        round_trip(&res);
+    }
 
-    fn query_alliance_params(
-        &self,
-    ) -> StdResult<ParamsResponse>;
+    #[cfg(feature = "rfc3339-time")]
+    #[test]
+    fn chain_alliances_response_deserializes() {
+        let res: AlliancesResponse = from_json(ALLIANCES_FIXTURE.as_bytes()).unwrap();
+        assert_eq!(res.alliances.len(), 2);
+        assert_eq!(res.pagination, Some(PaginationResponse { next_key: None, total: Some(2) }));
 
-    fn query_alliance_validator(
-        &self,
-        validator_addr: Addr,
-    ) -> StdResult<ValidatorResponse>;
+        let first = &res.alliances[0];
+        assert_eq!(first.denom, "ibc/B3504E092456BA618CC28AC671A71FB08C6CA0FD0BE7C8A5B5A3E2DD933CC9E4");
+        assert_eq!(first.reward_weight, dec("0.1"));
+        assert_eq!(first.consensus_cap, Some(dec("0.3")));
+        assert_eq!(first.total_validator_shares, dec("1523406.999999999999999998"));
+        assert_eq!(first.reward_start_time, Timestamp::from_nanos(1_686_076_649_956_787_974));
+        assert_eq!(first.last_reward_change_time, Timestamp::from_nanos(1_686_076_649_956_787_974));
+        assert_eq!(first.reward_change_interval, DurationSecs::from_secs(0));
+        assert!(first.initialized());
 
-    fn query_alliance_validators(
-        &self,
-        pagination: Option<Pagination>,
-    ) -> StdResult<ValidatorsResponse>;
-}
+        // Older chains leave out `consensus_cap`
+        let second = &res.alliances[1];
+        assert_eq!(second.consensus_cap, None);
+        assert_eq!(second.reward_start_time, Timestamp::from_nanos(1_688_169_600_100_000_000));
+        assert_eq!(second.last_reward_change_time, Timestamp::from_seconds(1_688_169_600));
+        assert_eq!(second.reward_change_interval, DurationSecs::from_secs(86_400));
+        assert!(!second.initialized());
 
-impl<'a, T> AllianceQuerier for QuerierWrapper<'a, T>
-where
-    T: CustomQuery + From<AllianceQuery>,
-{
-    fn query_alliance_alliance(
-        &self,
-        denom: String,
-    ) -> StdResult<AllianceResponse> {
-        let custom_query: T = AllianceQuery::Alliance { denom }.into();
-        self.query(&custom_query.into())
+        round_trip(&res);
     }
 
-    fn query_alliance_alliances(
-        &self,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesResponse> {
-        let custom_query: T = AllianceQuery::Alliances { pagination }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn chain_validator_response_deserializes() {
+        let res: ValidatorResponse = from_json(VALIDATOR_FIXTURE.as_bytes()).unwrap();
+        let denom = "ibc/B3504E092456BA618CC28AC671A71FB08C6CA0FD0BE7C8A5B5A3E2DD933CC9E4";
+        assert_eq!(res.validator_addr, Addr::unchecked("terravaloper1uxx32m0u5svtvrujnpk9ytlwjd75yh52j7qlkp"));
+        assert_eq!(res.total_staked_of(denom), dec("1523407"));
+        assert_eq!(res.validator_shares_of(denom), dec("1523406.999999999999999998"));
+        assert_eq!(res.total_delegation_shares_of(denom), dec("1523406.999999999999999998"));
+        round_trip(&res);
     }
 
-    fn query_alliance_alliances_delegations(
-        &self,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesDelegationsResponse> {
-        let custom_query: T = AllianceQuery::AlliancesDelegations { pagination }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn timestamps_keep_nanosecond_precision() {
+        let nanos = [
+            0,
+            1_686_076_649_000_000_000,
+            1_686_076_649_100_000_000,
+            1_686_076_649_956_787_000,
+            1_686_076_649_000_000_001,
+            1_686_076_649_956_787_974,
+        ];
+        for n in nanos {
+            let params = AllianceParams {
+                reward_delay_time: DurationSecs::from_secs(0),
+                take_rate_claim_interval: DurationSecs::from_secs(0),
+                last_take_rate_claim_time: Timestamp::from_nanos(n),
+            };
+            let json = to_json_string(&params).unwrap();
+            let parsed: AllianceParams = from_json(json.as_bytes()).unwrap();
+            assert_eq!(parsed.last_take_rate_claim_time.nanos(), n, "{}", json);
+        }
     }
 
-    fn query_alliance_alliances_delegation_by_validator(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        pagination: Option<Pagination>,
-    ) -> StdResult<AlliancesDelegationsResponse> {
-        let custom_query: T = AllianceQuery::AlliancesDelegationByValidator { delegator_addr, validator_addr, pagination }.into();
-        self.query(&custom_query.into())
+    #[cfg(feature = "rfc3339-time")]
+    #[test]
+    fn timestamps_outside_the_u64_range_fail_to_deserialize() {
+        for time in ["1969-12-31T23:59:59Z", "2554-07-21T23:34:33.709551616Z", "9999-12-31T23:59:59Z"] {
+            let json = format!(
+                r#"{{"reward_delay_time":0,"take_rate_claim_interval":0,"last_take_rate_claim_time":"{}"}}"#,
+                time
+            );
+            let err = from_json::<AllianceParams>(json.as_bytes()).unwrap_err();
+            assert!(err.to_string().contains("out of range"), "{}", err);
+        }
     }
 
-    fn query_alliance_delegation(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    ) -> StdResult<DelegationResponse> {
-        let custom_query: T = AllianceQuery::Delegation { delegator_addr, validator_addr, denom }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn reward_history_accepts_a_list() {
+        let delegation: Delegation = from_json(
+            br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":"10","reward_history":[{"denom":"uluna","index":"0.5"},null,{"denom":"uatom","index":"1"}],"last_reward_claim_height":null}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            delegation.reward_history,
+            Some(vec![
+                Some(Reward { denom: Some("uluna".to_string()), index: dec("0.5") }),
+                None,
+                Some(Reward { denom: Some("uatom".to_string()), index: dec("1") }),
+            ])
+        );
     }
 
-    fn query_alliance_delegation_rewards(
-        &self,
-        delegator_addr: Addr,
-        validator_addr: Addr,
-        denom: String,
-    ) -> StdResult<DelegationRewardsResponse> {
-        let custom_query: T = AllianceQuery::DelegationRewards { delegator_addr, validator_addr, denom }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn reward_history_accepts_a_denom_map() {
+        let delegation: Delegation = from_json(
+            br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":"10","reward_history":{"uluna":"0.5","uatom":1},"last_reward_claim_height":null}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            delegation.reward_history,
+            Some(vec![
+                Some(Reward { denom: Some("uatom".to_string()), index: dec("1") }),
+                Some(Reward { denom: Some("uluna".to_string()), index: dec("0.5") }),
+            ])
+        );
     }
 
-    fn query_alliance_params(
-        &self,
-    ) -> StdResult<ParamsResponse> {
-        let custom_query: T = AllianceQuery::Params { }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn reward_history_may_be_null_or_missing() {
+        let delegation: Delegation = from_json(
+            br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":"10","reward_history":null,"last_reward_claim_height":null}"#,
+        )
+        .unwrap();
+        assert_eq!(delegation.reward_history, None);
+        let delegation: Delegation =
+            from_json(br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":"10","last_reward_claim_height":null}"#)
+                .unwrap();
+        assert_eq!(delegation.reward_history, None);
+        from_json::<Delegation>(br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":"10","reward_history":"uluna","last_reward_claim_height":null}"#)
+            .unwrap_err();
     }
 
-    fn query_alliance_validator(
-        &self,
-        validator_addr: Addr,
-    ) -> StdResult<ValidatorResponse> {
-        let custom_query: T = AllianceQuery::Validator { validator_addr }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn u64_fields_accept_strings_and_numbers() {
+        let pagination: Pagination = from_json(br#"{"offset":"50","limit":10}"#).unwrap();
+        assert_eq!(pagination, Pagination { offset: Some(50), limit: Some(10), ..Default::default() });
+
+        let res: PaginationResponse = from_json(br#"{"next_key":null,"total":"18446744073709551615"}"#).unwrap();
+        assert_eq!(res.total, Some(u64::MAX));
+        let res: PaginationResponse = from_json(br#"{"next_key":null,"total":null}"#).unwrap();
+        assert_eq!(res.total, None);
+
+        let json = format!(
+            r#"{{"reward_delay_time":"86400","take_rate_claim_interval":300,"last_take_rate_claim_time":{}}}"#,
+            EXAMPLE_TIME_JSON
+        );
+        let params: AllianceParams = from_json(json.as_bytes()).unwrap();
+        assert_eq!(params.reward_delay_time, DurationSecs::from_secs(86_400));
+        assert_eq!(params.take_rate_claim_interval, DurationSecs::from_secs(300));
     }
 
-    fn query_alliance_validators(
-        &self,
-        pagination: Option<Pagination>
-    ) -> StdResult<ValidatorsResponse> {
-        let custom_query: T = AllianceQuery::Validators { pagination }.into();
-        self.query(&custom_query.into())
+    #[test]
+    fn u64_fields_reject_other_values() {
+        for offset in [r#""-1""#, r#""1.5""#, r#""""#, "-1", "true", r#""18446744073709551616""#] {
+            let json = format!(r#"{{"offset":{}}}"#, offset);
+            assert!(from_json::<Pagination>(json.as_bytes()).is_err(), "{}", json);
+        }
     }
 
-}
+    fn delegation_with_history(history: Vec<Option<Reward>>) -> Delegation {
+        Delegation { reward_history: Some(history), ..delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 10).delegation }
+    }
 
-// This export is added to all contracts that import this package, signifying that they require
-// "alliance" support on the chain they run on.
-#[no_mangle]
-extern "C" fn requires_alliance() {}
+    #[test]
+    fn duplicate_reward_denoms_resolve_to_the_highest_index() {
+        let mut delegation = delegation_with_history(vec![
+            reward(Some("uluna"), "0.5"),
+            None,
+            reward(Some("uluna"), "0.7"),
+            reward(None, "9"),
+            reward(Some("uluna"), "0.6"),
+        ]);
+        assert_eq!(delegation.reward_index("uluna"), Some(dec("0.7")));
+        assert_eq!(delegation.reward_index_map(), BTreeMap::from([("uluna".to_string(), dec("0.7"))]));
+        assert_eq!(delegation.pending_reward("uluna", dec("1")), dec("3"));
+
+        delegation.dedup_reward_history();
+        assert_eq!(delegation.reward_history, Some(vec![reward(Some("uluna"), "0.7"), reward(None, "9")]));
+        assert_eq!(delegation.reward_index("uluna"), Some(dec("0.7")));
+    }
+
+    #[test]
+    fn total_tokens_by_denom_sums_and_saturates() {
+        let res = AlliancesResponse {
+            alliances: vec![
+                AllianceAsset { total_tokens: dec("100"), ..alliance_asset("uluna") },
+                AllianceAsset { total_tokens: dec("50"), ..alliance_asset("uatom") },
+                AllianceAsset { total_tokens: dec("25"), ..alliance_asset("uluna") },
+            ],
+            pagination: None,
+        };
+        assert_eq!(
+            res.total_tokens_by_denom(),
+            BTreeMap::from([("uatom".to_string(), dec("50")), ("uluna".to_string(), dec("125"))])
+        );
+
+        let res = AlliancesResponse {
+            alliances: vec![
+                AllianceAsset { total_tokens: Decimal256::MAX, ..alliance_asset("uluna") },
+                AllianceAsset { total_tokens: dec("1"), ..alliance_asset("uluna") },
+            ],
+            pagination: None,
+        };
+        assert_eq!(res.total_tokens_by_denom(), BTreeMap::from([("uluna".to_string(), Decimal256::MAX)]));
+    }
+
+    #[test]
+    fn coin_sums_merge_denoms_and_saturate() {
+        let responses = [
+            DelegationRewardsResponse { rewards: vec![Coin::new(10, "uluna"), Coin::new(5, "uatom"), Coin::new(1, "uluna")] },
+            DelegationRewardsResponse { rewards: vec![Coin::new(4, "uatom"), Coin::new(u128::MAX, "ubig"), Coin::new(1, "ubig")] },
+        ];
+        let expected = BTreeMap::from([
+            ("uatom".to_string(), Uint128::new(9)),
+            ("ubig".to_string(), Uint128::MAX),
+            ("uluna".to_string(), Uint128::new(11)),
+        ]);
+        assert_eq!(sum_rewards(&responses), expected);
+        assert_eq!(
+            responses[0].total_by_denom(),
+            BTreeMap::from([("uatom".to_string(), Uint128::new(5)), ("uluna".to_string(), Uint128::new(11))])
+        );
+
+        let delegations = vec![
+            delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", u128::MAX),
+            delegation("cosmos1delegator", "cosmosvaloper1b", "uluna", 1),
+            delegation("cosmos1delegator", "cosmosvaloper1a", "uatom", 7),
+        ];
+        let expected = BTreeMap::from([("uatom".to_string(), Uint128::new(7)), ("uluna".to_string(), Uint128::MAX)]);
+        let res = AlliancesDelegationsResponse { delegations: Some(delegations), pagination: None };
+        assert_eq!(res.total_balance_by_denom(), expected);
+        assert!(AlliancesDelegationsResponse { delegations: None, pagination: None }.total_balance_by_denom().is_empty());
+    }
+
+    #[test]
+    fn duration_constructors_check_for_overflow() {
+        assert_eq!(DurationSecs::from_hours(2), Some(DurationSecs::from_secs(7200)));
+        assert_eq!(DurationSecs::from_days(1), Some(DurationSecs::from_secs(86_400)));
+        assert_eq!(DurationSecs::from_hours(u64::MAX / 3600 + 1), None);
+        assert_eq!(DurationSecs::from_days(u64::MAX / 86_400 + 1), None);
+        assert_eq!(DurationSecs::from_days(u64::MAX / 86_400).map(|d| d.as_secs()), Some(u64::MAX / 86_400 * 86_400));
+    }
+
+    // `DurationSecs` must stay a bare number of seconds on the wire, like the `u64`s it replaced
+    #[test]
+    fn durations_keep_the_u64_wire_format() {
+        assert_eq!(to_json_string(&DurationSecs::from_secs(300)).unwrap(), "300");
+        assert_eq!(from_json::<DurationSecs>(b"300").unwrap(), DurationSecs::from_secs(300));
+        assert_eq!(from_json::<DurationSecs>(br#""300""#).unwrap(), DurationSecs::from_secs(300));
+
+        let params = AllianceParams {
+            reward_delay_time: DurationSecs::from_secs(86_400),
+            take_rate_claim_interval: DurationSecs::from_secs(300),
+            last_take_rate_claim_time: Timestamp::from_nanos(1_686_076_649_956_787_974),
+        };
+        let json = format!(
+            r#"{{"reward_delay_time":86400,"take_rate_claim_interval":300,"last_take_rate_claim_time":{}}}"#,
+            EXAMPLE_TIME_JSON
+        );
+        assert_eq!(to_json_string(&params).unwrap(), json);
+        assert_eq!(from_json::<AllianceParams>(json.as_bytes()).unwrap(), params);
+    }
+
+    #[test]
+    fn addresses_serialize_as_plain_strings() {
+        let validator = ValidatorAddr::from("cosmosvaloper1qs8tnw2t8l6amtzvdemnnsq9dzk0ag0z52uzay");
+        assert_eq!(to_json_string(&validator).unwrap(), r#""cosmosvaloper1qs8tnw2t8l6amtzvdemnnsq9dzk0ag0z52uzay""#);
+        round_trip(&validator);
+        assert_eq!(ValidatorAddr::from(Addr::unchecked("cosmosvaloper1a")), ValidatorAddr::from("cosmosvaloper1a".to_string()));
+        assert_eq!(validator.to_string(), validator.as_str());
+
+        let delegator = DelegatorAddr::from(Addr::unchecked("cosmos1delegator"));
+        assert_eq!(to_json_string(&delegator).unwrap(), r#""cosmos1delegator""#);
+        round_trip(&delegator);
+        assert_eq!(delegator.as_str(), "cosmos1delegator");
+        assert_eq!(Addr::from(delegator), Addr::unchecked("cosmos1delegator"));
+    }
+
+    #[test]
+    fn pagination_builder_sets_one_field_per_setter() {
+        let key = Binary::from(b"key".as_slice());
+        assert_eq!(Pagination::builder().build(), Pagination::default());
+        assert_eq!(Pagination::builder().key(key.clone()).build(), Pagination { key: Some(key), ..Default::default() });
+        assert_eq!(Pagination::builder().offset(5).build(), Pagination { offset: Some(5), ..Default::default() });
+        assert_eq!(Pagination::builder().limit(10).build(), Pagination { limit: Some(10), ..Default::default() });
+        assert_eq!(Pagination::builder().count_total(true).build(), Pagination { count_total: Some(true), ..Default::default() });
+        assert_eq!(Pagination::builder().reverse(true).build(), Pagination { reverse: Some(true), ..Default::default() });
+        assert_eq!(Pagination::with_limit(10), Pagination::builder().limit(10).build());
+    }
+
+    #[test]
+    fn pagination_leaves_out_unset_fields() {
+        assert_eq!(to_json_string(&Pagination::with_limit(10)).unwrap(), r#"{"limit":10}"#);
+        assert_eq!(to_json_string(&Pagination::default()).unwrap(), "{}");
+        round_trip(&Pagination::builder().offset(5).count_total(true).reverse(false).build());
+    }
+
+    #[test]
+    fn pagination_accepts_camel_case() {
+        let res: PaginationResponse = from_json(br#"{"nextKey":"AQ==","total":"3"}"#).unwrap();
+        assert_eq!(res, PaginationResponse { next_key: Some(Binary::from([1u8])), total: Some(3) });
+        assert_eq!(to_json_string(&res).unwrap(), r#"{"next_key":"AQ==","total":3}"#);
+
+        let pagination: Pagination = from_json(br#"{"countTotal":true}"#).unwrap();
+        assert_eq!(pagination, Pagination { count_total: Some(true), ..Default::default() });
+    }
+
+    #[test]
+    fn delegation_accepts_camel_case() {
+        let delegation: Delegation = from_json(
+            br#"{"delegatorAddress":"cosmos1delegator","validatorAddress":"cosmosvaloper1a","denom":"uluna","shares":"1","rewardHistory":null,"lastRewardClaimHeight":5}"#,
+        )
+        .unwrap();
+        assert_eq!(delegation.delegator_address, Some(Addr::unchecked("cosmos1delegator")));
+        assert_eq!(delegation.validator_address, Some(Addr::unchecked("cosmosvaloper1a")));
+        assert_eq!(delegation.last_reward_claim_height, Some(5));
+        assert!(to_json_string(&delegation).unwrap().contains(r#""delegator_address":"cosmos1delegator""#));
+    }
+
+    #[test]
+    fn duration_arithmetic() {
+        let secs = DurationSecs::from_secs;
+        assert_eq!(secs(10).checked_add(secs(5)), Some(secs(15)));
+        assert_eq!(secs(u64::MAX).checked_add(secs(1)), None);
+        assert_eq!(secs(10).checked_sub(secs(11)), None);
+        assert_eq!(secs(10).checked_mul(3), Some(secs(30)));
+        assert_eq!(secs(u64::MAX).saturating_add(secs(1)), secs(u64::MAX));
+        assert_eq!(secs(10).saturating_sub(secs(11)), secs(0));
+        assert_eq!(secs(300).to_string(), "300s");
+        assert_eq!(secs(2).after(Timestamp::from_seconds(1)), Ok(Timestamp::from_seconds(3)));
+        assert!(secs(u64::MAX).after(Timestamp::from_seconds(1)).is_err());
+    }
+
+    #[test]
+    fn take_rate_claim_times() {
+        let params = params();
+        let next = Timestamp::from_seconds(1_686_076_949);
+        assert_eq!(params.next_take_rate_claim_time(), Ok(next));
+        assert!(!params.is_take_rate_claimable(next.minus_nanos(1)));
+        assert!(params.is_take_rate_claimable(next));
+        assert!(params.is_take_rate_claimable(next.plus_seconds(1)));
+
+        let params = AllianceParams { take_rate_claim_interval: DurationSecs::from_secs(u64::MAX), ..params };
+        assert!(params.next_take_rate_claim_time().is_err());
+        assert!(!params.is_take_rate_claimable(Timestamp::from_nanos(u64::MAX)));
+    }
+
+    #[test]
+    fn reward_unlock_times() {
+        let params = params();
+        let start = Timestamp::from_seconds(1_686_076_649);
+        let unlock = start.plus_seconds(86_400);
+        assert_eq!(params.reward_unlock_time(start), unlock);
+        assert!(!params.is_reward_unlocked(start, unlock.minus_nanos(1)));
+        assert!(params.is_reward_unlocked(start, unlock));
+        assert!(params.is_reward_unlocked(start, unlock.plus_seconds(1)));
+        assert_eq!(params.reward_unlock_time(Timestamp::from_nanos(u64::MAX - 1)), Timestamp::from_nanos(u64::MAX));
+    }
+
+    #[test]
+    fn dec_coin_denom_accessors() {
+        assert_eq!(dec_coin(Some("uluna"), "1").denom_str(), "uluna");
+        assert_eq!(dec_coin(Some("uluna"), "1").require_denom(), Ok("uluna"));
+        assert_eq!(dec_coin(None, "1").denom_str(), "");
+        assert_eq!(dec_coin(None, "1").require_denom(), Err(AllianceError::MissingDenom {}));
+    }
+
+    #[test]
+    fn dec_coin_to_coin_rounds() {
+        assert_eq!(dec_coin(Some("uluna"), "1.9").to_coin_floor(), Ok(Coin::new(1, "uluna")));
+        assert_eq!(dec_coin(Some("uluna"), "1.9").to_coin_ceil(), Ok(Coin::new(2, "uluna")));
+        assert_eq!(dec_coin(Some("uluna"), "5").to_coin_floor(), Ok(Coin::new(5, "uluna")));
+        assert_eq!(dec_coin(Some("uluna"), "5").to_coin_ceil(), Ok(Coin::new(5, "uluna")));
+        assert_eq!(dec_coin(None, "5").to_coin_floor(), Err(AllianceError::MissingDenom {}));
+
+        let too_large = Decimal256::from_ratio(Uint256::from(u128::MAX) + Uint256::one(), 1u128);
+        let coin = DecCoin { denom: Some("uluna".to_string()), amount: too_large };
+        assert_eq!(coin.to_coin_floor(), Err(AllianceError::DecimalOverflow { value: too_large.to_string() }));
+        let coin = DecCoin { amount: Decimal256::from_ratio(u128::MAX, 1u128) + dec("0.5"), ..coin };
+        assert!(matches!(coin.to_coin_ceil(), Err(AllianceError::DecimalOverflow { .. })));
+    }
+
+    #[test]
+    fn coin_conversions() {
+        assert_eq!(DecCoin::from(Coin::new(5, "uluna")), dec_coin(Some("uluna"), "5"));
+        assert_eq!(Coin::try_from(dec_coin(Some("uluna"), "5")), Ok(Coin::new(5, "uluna")));
+        assert_eq!(Coin::try_from(dec_coin(None, "5")), Err(AllianceError::MissingDenom {}));
+        assert_eq!(
+            Coin::try_from(dec_coin(Some("uluna"), "1.5")),
+            Err(AllianceError::NonIntegerAmount { amount: "1.5".to_string() })
+        );
+    }
+
+    #[test]
+    fn dec_coin_sums() {
+        let coins = [
+            dec_coin(Some("uluna"), "1"),
+            dec_coin(Some("uatom"), "2"),
+            dec_coin(Some("uluna"), "0.5"),
+            dec_coin(None, "3"),
+        ];
+        assert_eq!(sum_dec_coins(&coins, "uluna"), dec("1.5"));
+        assert_eq!(sum_dec_coins(&coins, "uosmo"), Decimal256::zero());
+        assert_eq!(
+            group_dec_coins(&coins),
+            BTreeMap::from([("uatom".to_string(), dec("2")), ("uluna".to_string(), dec("1.5"))])
+        );
+        let overflowing = [dec_coin(Some("uluna"), "1"), DecCoin { amount: Decimal256::MAX, ..dec_coin(Some("uluna"), "0") }];
+        assert_eq!(sum_dec_coins(&overflowing, "uluna"), Decimal256::MAX);
+    }
+
+    #[test]
+    fn decimals_accept_unquoted_numbers() {
+        let coin: DecCoin = from_json(br#"{"denom":"uluna","amount":15}"#).unwrap();
+        assert_eq!(coin, dec_coin(Some("uluna"), "15"));
+        let coin: DecCoin = from_json(br#"{"denom":"uluna","amount":"1.5"}"#).unwrap();
+        assert_eq!(coin, dec_coin(Some("uluna"), "1.5"));
+        // Fractions need the number's text, which serde_json exposes with `arbitrary_precision`
+        let coin: DecCoin = serde_json::from_slice(br#"{"denom":"uluna","amount":1.5}"#).unwrap();
+        assert_eq!(coin, dec_coin(Some("uluna"), "1.5"));
+        let coin: DecCoin = serde_json::from_slice(br#"{"denom":"uluna","amount":0.123456789012345678}"#).unwrap();
+        assert_eq!(coin, dec_coin(Some("uluna"), "0.123456789012345678"));
+        from_json::<DecCoin>(br#"{"denom":"uluna","amount":"abc"}"#).unwrap_err();
+        serde_json::from_slice::<DecCoin>(br#"{"denom":"uluna","amount":-1.5}"#).unwrap_err();
+        serde_json::from_slice::<DecCoin>(br#"{"denom":"uluna","amount":{"value":1}}"#).unwrap_err();
+    }
+
+    #[cfg(feature = "lenient-decimals")]
+    #[test]
+    fn lenient_decimals_read_null_as_zero() {
+        let coin: DecCoin = from_json(br#"{"denom":"uluna","amount":null}"#).unwrap();
+        assert_eq!(coin.amount, Decimal256::zero());
+        let delegation: Delegation = from_json(
+            br#"{"delegator_address":null,"validator_address":null,"denom":"uluna","shares":null,"last_reward_claim_height":null}"#,
+        )
+        .unwrap();
+        assert_eq!(delegation.shares, Decimal256::zero());
+        // Fields where zero is no sensible default still require a value
+        from_json::<Reward>(br#"{"denom":"uluna","index":null}"#).unwrap_err();
+    }
+
+    #[cfg(not(feature = "lenient-decimals"))]
+    #[test]
+    fn null_decimals_are_rejected() {
+        from_json::<DecCoin>(br#"{"denom":"uluna","amount":null}"#).unwrap_err();
+        from_json::<DecCoin>(br#"{"denom":"uluna","amount":"1e-3"}"#).unwrap_err();
+        serde_json::from_slice::<DecCoin>(br#"{"denom":"uluna","amount":1e-3}"#).unwrap_err();
+    }
+
+    #[cfg(feature = "lenient-decimals")]
+    #[test]
+    fn lenient_decimals_accept_scientific_notation() {
+        for (input, expected) in [("1e-3", "0.001"), ("1.5e2", "150"), ("1E-18", "0.000000000000000001"), ("2.50e0", "2.5")] {
+            let json = format!(r#"{{"denom":"uluna","amount":"{}"}}"#, input);
+            assert_eq!(from_json::<DecCoin>(json.as_bytes()).unwrap().amount, dec(expected), "{}", input);
+        }
+        for input in ["1e-19", "e5", "1e", "1.5e2.5", "1e1000"] {
+            let json = format!(r#"{{"denom":"uluna","amount":"{}"}}"#, input);
+            assert!(from_json::<DecCoin>(json.as_bytes()).is_err(), "{}", input);
+        }
+    }
+
+    #[cfg(feature = "lenient-decimals")]
+    #[test]
+    fn lenient_decimals_accept_unquoted_scientific_notation() {
+        for (input, expected) in [("1e-3", "0.001"), ("1.5e2", "150"), ("1e-18", "0.000000000000000001")] {
+            let json = format!(r#"{{"denom":"uluna","amount":{}}}"#, input);
+            assert_eq!(serde_json::from_str::<DecCoin>(&json).unwrap().amount, dec(expected), "{}", input);
+        }
+        serde_json::from_slice::<DecCoin>(br#"{"denom":"uluna","amount":1e-19}"#).unwrap_err();
+    }
+
+    #[test]
+    fn validator_amounts_by_denom() {
+        let mut validator = validator("cosmosvaloper1a");
+        validator.total_staked.push(dec_coin(Some("uluna"), "50"));
+        validator.total_staked.push(dec_coin(Some("uatom"), "7"));
+        assert_eq!(validator.total_staked_of("uluna"), dec("250"));
+        assert_eq!(validator.total_staked_of("uatom"), dec("7"));
+        assert_eq!(validator.total_staked_of("uosmo"), Decimal256::zero());
+        assert_eq!(validator.validator_shares_of("uluna"), dec("50"));
+        assert_eq!(validator.total_delegation_shares_of("uluna"), dec("100.5"));
+    }
+
+    #[test]
+    fn validator_exchange_rates() {
+        let validator = validator("cosmosvaloper1a");
+        assert_eq!(validator.exchange_rate("uluna"), Ok(dec("4")));
+        assert_eq!(validator.shares_to_tokens("uluna", dec("10")), Ok(dec("40")));
+        assert_eq!(validator.exchange_rate("uatom"), Err(AllianceError::ZeroShares { denom: "uatom".to_string() }));
+        assert_eq!(
+            validator.shares_to_tokens("uatom", dec("10")),
+            Err(AllianceError::ZeroShares { denom: "uatom".to_string() })
+        );
+
+        let one_to_one =
+            ValidatorResponse { total_staked: vec![dec_coin(Some("uluna"), "50")], ..self::validator("cosmosvaloper1a") };
+        assert_eq!(one_to_one.exchange_rate("uluna"), Ok(Decimal256::one()));
+        assert_eq!(one_to_one.shares_to_tokens("uluna", dec("10")), Ok(dec("10")));
+    }
+
+    #[test]
+    fn validators_by_address() {
+        let res = ValidatorsResponse {
+            validators: vec![validator("cosmosvaloper1a"), validator("cosmosvaloper1b"), validator("cosmosvaloper1c")],
+            pagination: None,
+        };
+        let index = res.by_address();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.get(&Addr::unchecked("cosmosvaloper1b")), Some(&&res.validators[1]));
+        assert_eq!(index.get(&Addr::unchecked("cosmosvaloper1d")), None);
+    }
+
+    #[test]
+    fn weight_ranges() {
+        let range = WeightRange::new(dec("0.05"), dec("0.2")).unwrap();
+        assert!(!range.contains(dec("0.04")));
+        assert!(range.contains(dec("0.05")));
+        assert!(range.contains(dec("0.1")));
+        assert!(range.contains(dec("0.2")));
+        assert!(!range.contains(dec("0.21")));
+        assert_eq!(range.clamp(dec("0.01")), dec("0.05"));
+        assert_eq!(range.clamp(dec("0.1")), dec("0.1"));
+        assert_eq!(range.clamp(dec("0.3")), dec("0.2"));
+
+        assert!(WeightRange::new(dec("0.1"), dec("0.1")).is_ok());
+        assert_eq!(
+            WeightRange::new(dec("0.2"), dec("0.1")),
+            Err(AllianceError::InvalidWeightRange { min: "0.2".to_string(), max: "0.1".to_string() })
+        );
+    }
+
+    #[test]
+    fn alliance_initialized_states() {
+        assert!(AllianceAsset { is_initialized: Some(true), ..alliance_asset("uluna") }.initialized());
+        assert!(!AllianceAsset { is_initialized: Some(false), ..alliance_asset("uluna") }.initialized());
+        assert!(!AllianceAsset { is_initialized: None, ..alliance_asset("uluna") }.initialized());
+    }
+
+    #[test]
+    fn alliance_reward_weight_in_range() {
+        assert!(alliance_asset("uluna").reward_weight_in_range());
+        assert!(!AllianceAsset { reward_weight: dec("0.3"), ..alliance_asset("uluna") }.reward_weight_in_range());
+    }
+
+    #[test]
+    fn alliance_tokens_after_take_rate() {
+        let asset = AllianceAsset { take_rate: dec("0.5"), total_tokens: dec("1000"), ..alliance_asset("uluna") };
+        assert_eq!(asset.tokens_after_take_rate(0), dec("1000"));
+        assert_eq!(asset.tokens_after_take_rate(1), dec("500"));
+        assert_eq!(asset.tokens_after_take_rate(3), dec("125"));
+        assert_eq!(asset.tokens_after_take_rate(u64::MAX), Decimal256::zero());
+        assert_eq!(AllianceAsset { take_rate: Decimal256::zero(), ..asset.clone() }.tokens_after_take_rate(u64::MAX), dec("1000"));
+        assert_eq!(AllianceAsset { take_rate: Decimal256::one(), ..asset }.tokens_after_take_rate(1), Decimal256::zero());
+    }
+
+    #[test]
+    fn alliance_consensus_power() {
+        let res = AlliancesResponse {
+            alliances: vec![
+                alliance_asset("uluna"),
+                AllianceAsset { total_tokens: dec("100"), consensus_weight: dec("2"), ..alliance_asset("uatom") },
+            ],
+            pagination: None,
+        };
+        assert_eq!(res.alliances[0].consensus_power(), dec("500000"));
+        assert_eq!(res.alliances[1].consensus_power(), dec("200"));
+        assert_eq!(res.total_consensus_power(), dec("500200"));
+        let saturating = AllianceAsset { total_tokens: Decimal256::MAX, consensus_weight: dec("2"), ..alliance_asset("uluna") };
+        assert_eq!(saturating.consensus_power(), Decimal256::MAX);
+    }
+
+    #[test]
+    fn alliance_estimated_apr() {
+        let asset = alliance_asset("uluna");
+        assert_eq!(asset.estimated_apr(dec("1000"), dec("0.07")), dec("0.000007"));
+        assert_eq!(asset.estimated_apr(dec("0.5"), dec("0.07")), dec("0.014"));
+        assert_eq!(asset.estimated_apr(Decimal256::zero(), dec("0.07")), Decimal256::zero());
+    }
+
+    #[test]
+    fn alliance_projected_reward_weight() {
+        let increasing = AllianceAsset { reward_change_rate: dec("1.1"), ..alliance_asset("uluna") };
+        assert_eq!(increasing.projected_reward_weight(0), dec("0.1"));
+        assert_eq!(increasing.projected_reward_weight(1), dec("0.11"));
+        assert_eq!(increasing.projected_reward_weight(10), dec("0.2"));
+        let doubling = AllianceAsset { reward_change_rate: dec("2"), ..alliance_asset("uluna") };
+        assert_eq!(doubling.projected_reward_weight(u64::MAX), dec("0.2"));
+
+        let decreasing = AllianceAsset { reward_change_rate: dec("0.9"), ..alliance_asset("uluna") };
+        assert_eq!(decreasing.projected_reward_weight(1), dec("0.09"));
+        assert_eq!(decreasing.projected_reward_weight(10), dec("0.05"));
+        assert_eq!(decreasing.projected_reward_weight(u64::MAX), dec("0.05"));
+    }
+
+    #[test]
+    fn alliance_reward_start() {
+        let asset = alliance_asset("uluna");
+        let start = asset.reward_start_time;
+        assert!(!asset.rewards_started(start.minus_nanos(1)));
+        assert!(asset.rewards_started(start));
+        assert!(asset.rewards_started(start.plus_seconds(1)));
+        assert_eq!(asset.time_until_rewards(start.minus_nanos(1_500_000_000)), 2);
+        assert_eq!(asset.time_until_rewards(start.minus_seconds(2)), 2);
+        assert_eq!(asset.time_until_rewards(start), 0);
+        assert_eq!(asset.time_until_rewards(start.plus_seconds(1)), 0);
+    }
+
+    #[cfg(feature = "rfc3339-time")]
+    #[test]
+    fn alliance_times_serialize_as_rfc3339() {
+        let json = to_json_string(&alliance_asset("uluna")).unwrap();
+        assert!(json.contains(r#""reward_start_time":"2023-06-06T18:37:29.956787974Z""#), "{}", json);
+        assert!(json.contains(r#""last_reward_change_time":"2023-06-07T18:37:29.956787974Z""#), "{}", json);
+        from_json::<AllianceParams>(br#"{"reward_delay_time":0,"take_rate_claim_interval":0,"last_take_rate_claim_time":"1686076649956787974"}"#)
+            .unwrap_err();
+    }
+
+    #[cfg(not(feature = "rfc3339-time"))]
+    #[test]
+    fn alliance_times_serialize_as_nanos() {
+        let json = to_json_string(&alliance_asset("uluna")).unwrap();
+        assert!(json.contains(r#""reward_start_time":1686076649956787974,"#), "{}", json);
+        assert!(json.contains(r#""last_reward_change_time":1686163049956787974,"#), "{}", json);
+        // cosmwasm's own `Timestamp` encoding quotes the nanoseconds
+        let params: AllianceParams =
+            from_json(br#"{"reward_delay_time":0,"take_rate_claim_interval":0,"last_take_rate_claim_time":"1686076649956787974"}"#)
+                .unwrap();
+        assert_eq!(params.last_take_rate_claim_time, Timestamp::from_nanos(1_686_076_649_956_787_974));
+        from_json::<AllianceParams>(
+            br#"{"reward_delay_time":0,"take_rate_claim_interval":0,"last_take_rate_claim_time":"2023-06-06T18:37:29.956787974Z"}"#,
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn ibc_denoms() {
+        let hash = "B3504E092456BA618CC28AC671A71FB08C6CA0FD0BE7C8A5B5A3E2DD933CC9E4";
+        assert!(is_ibc_denom(&format!("ibc/{}", hash)));
+        assert!(!is_ibc_denom(&format!("ibc/{}", &hash[1..])));
+        assert!(!is_ibc_denom(&format!("ibc/{}0", hash)));
+        assert!(!is_ibc_denom(&format!("ibc/{}", hash.to_lowercase())));
+        assert!(!is_ibc_denom(hash));
+        assert!(!is_ibc_denom("uluna"));
+        assert_eq!(validate_ibc_denom(&format!("ibc/{}", hash)), Ok(()));
+        assert_eq!(validate_ibc_denom("uluna"), Err(AllianceError::InvalidDenom { denom: "uluna".to_string() }));
+    }
+
+    fn mixed_alliances() -> Vec<AllianceAsset> {
+        vec![
+            AllianceAsset { is_initialized: Some(true), ..alliance_asset("uosmo") },
+            AllianceAsset { is_initialized: Some(false), ..alliance_asset("uatom") },
+            AllianceAsset { is_initialized: None, ..alliance_asset("uluna") },
+        ]
+    }
+
+    #[test]
+    fn alliance_lists() {
+        let mut alliances = mixed_alliances();
+        sort_alliances_by_denom(&mut alliances);
+        assert_eq!(alliances.iter().map(|a| a.denom.as_str()).collect::<Vec<_>>(), ["uatom", "uluna", "uosmo"]);
+
+        let initialized = initialized_alliances(&alliances);
+        assert_eq!(initialized.len(), 1);
+        assert_eq!(initialized[0].denom, "uosmo");
+        assert_eq!(filter_initialized(alliances), vec![AllianceAsset { is_initialized: Some(true), ..alliance_asset("uosmo") }]);
+    }
+
+    #[test]
+    fn alliances_response_lookups() {
+        let res = AlliancesResponse { alliances: mixed_alliances(), pagination: None };
+        assert_eq!(res.uninitialized_denoms(), vec!["uatom".to_string(), "uluna".to_string()]);
+        assert_eq!(res.find("uatom").map(|a| a.denom.as_str()), Some("uatom"));
+        assert_eq!(res.find("ujuno"), None);
+        let index = res.index_by_denom();
+        assert_eq!(index.keys().copied().collect::<Vec<_>>(), ["uatom", "uluna", "uosmo"]);
+        assert_eq!(index.get("uluna"), Some(&&res.alliances[2]));
+        assert_eq!(AllianceAsset::from(AllianceResponse { alliance: alliance_asset("uluna") }), alliance_asset("uluna"));
+    }
+
+    #[test]
+    fn small_responses_round_trip() {
+        let res: RewardRateResponse = from_json(br#"{"denom":"uluna","rate":"0.073500000000000000"}"#).unwrap();
+        assert_eq!(res, RewardRateResponse { denom: "uluna".to_string(), rate: dec("0.0735") });
+        round_trip(&res);
+
+        let res: RewardPoolResponse = from_json(br#"{"denom":"uluna","balance":"1523407.123456789012345678"}"#).unwrap();
+        assert_eq!(res.balance, dec("1523407.123456789012345678"));
+        round_trip(&res);
+
+        round_trip(&UnbondingDelegationsResponse {
+            unbondings: vec![UnbondingDelegation {
+                validator_addr: "cosmosvaloper1a".into(),
+                denom: "uluna".to_string(),
+                amount: Uint128::new(1000),
+                completion_time: Timestamp::from_nanos(1_686_076_649_956_787_974),
+            }],
+            pagination: Some(PaginationResponse { next_key: None, total: Some(1) }),
+        });
+    }
+
+    #[test]
+    fn missing_delegations_deserialize_as_none() {
+        let res: AlliancesDelegationsResponse = from_json(b"{}").unwrap();
+        assert_eq!(res, AlliancesDelegationsResponse { delegations: None, pagination: None });
+        assert!(res.delegations_or_empty().is_empty());
+
+        let delegations = vec![delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 10)];
+        let res = AlliancesDelegationsResponse { delegations: Some(delegations.clone()), pagination: None };
+        assert_eq!(res.delegations_or_empty(), delegations.as_slice());
+    }
+
+    #[test]
+    fn delegation_accessors() {
+        let res: DelegationResponse = from_json(
+            br#"{"delegation":{"delegation":{"delegator_address":"cosmos1delegator","validator_address":"cosmosvaloper1a","denom":"uluna","shares":"10.500000000000000000","reward_history":[],"last_reward_claim_height":42},"balance":{"denom":"uluna","amount":"10"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(res.inner_delegation().shares, dec("10.5"));
+        assert_eq!(res.inner_delegation().last_reward_claim_height, Some(42));
+        assert_eq!(res.delegation_balance(), &Coin::new(10, "uluna"));
+        assert_eq!(res.delegation.shares(), dec("10.5"));
+        assert_eq!(res.delegation.balance(), &Coin::new(10, "uluna"));
+    }
+
+    #[test]
+    fn reward_lookups_skip_empty_slots() {
+        let delegation = delegation_with_history(vec![
+            None,
+            reward(Some("uluna"), "0.25"),
+            None,
+            reward(None, "9"),
+            reward(Some("uatom"), "1.5"),
+        ]);
+        assert_eq!(delegation.rewards_iter().count(), 3);
+        assert_eq!(
+            delegation.reward_index_map(),
+            BTreeMap::from([("uatom".to_string(), dec("1.5")), ("uluna".to_string(), dec("0.25"))])
+        );
+        assert_eq!(delegation.reward_index("uatom"), Some(dec("1.5")));
+        assert_eq!(delegation.reward_index("uosmo"), None);
+        assert_eq!(Delegation { reward_history: None, ..delegation }.rewards_iter().count(), 0);
+    }
+
+    #[test]
+    fn pending_rewards() {
+        // 1000 shares with a stored uluna index of 0.25
+        let delegation = delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 1000).delegation;
+        assert_eq!(delegation.pending_reward("uluna", dec("0.75")), dec("500"));
+        assert_eq!(delegation.pending_reward("uatom", dec("0.1")), dec("100"));
+        assert_eq!(delegation.pending_reward("uluna", dec("0.1")), Decimal256::zero());
+        assert_eq!(delegation.pending_reward("uatom", Decimal256::MAX), Decimal256::MAX);
+    }
+
+    #[test]
+    fn reward_denom_accessors() {
+        let with_denom = reward(Some("uluna"), "1").unwrap();
+        assert_eq!(with_denom.denom_str(), "uluna");
+        assert_eq!(with_denom.require_denom(), Ok("uluna"));
+        let without_denom = reward(None, "1").unwrap();
+        assert_eq!(without_denom.denom_str(), "");
+        assert_eq!(without_denom.require_denom(), Err(AllianceError::MissingDenom {}));
+    }
+
+    #[test]
+    fn dec_approx_eq_boundaries() {
+        let epsilon = dec("0.1");
+        assert!(dec_approx_eq(dec("1"), dec("1"), epsilon));
+        assert!(dec_approx_eq(dec("1"), dec("1.1"), epsilon));
+        assert!(dec_approx_eq(dec("1.1"), dec("1"), epsilon));
+        assert!(dec_approx_eq(dec("1"), dec("1.099999999999999999"), epsilon));
+        assert!(!dec_approx_eq(dec("1"), dec("1.100000000000000001"), epsilon));
+        assert!(!dec_approx_eq(dec("1.100000000000000001"), dec("1"), epsilon));
+    }
+}
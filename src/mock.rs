@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    to_json_binary, Binary, Coin, ContractResult, CustomQuery, Decimal256, OwnedDeps, QuerierResult, SystemError,
+    SystemResult,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::{
+    AllianceAsset, AllianceParams, AllianceQuery, AllianceQueryWrapper, AllianceResponse, AlliancesDelegationsResponse,
+    AlliancesResponse, DelegationResponse, DelegationRewardsResponse, Pagination, PaginationResponse,
+    ParamsResponse, RewardPoolResponse, RewardRateResponse, SingleDelegationResponse, UnbondingDelegation,
+    UnbondingDelegationsResponse, ValidatorResponse, ValidatorsResponse,
+};
+
+type DelegationKey = (String, String, String);
+
+/// The custom query type the mocks use by default, an alias for `AllianceQueryWrapper`.
+/// Contracts with their own custom query enum can use that enum with the mocks instead,
+/// as long as it implements `TryInto<AllianceQuery>`.
+pub type AllianceCustomQuery = AllianceQueryWrapper;
+
+/// Canned chain state that alliance queries are answered from.
+/// Delegations and rewards are keyed by `(delegator, validator, denom)`.
+#[derive(Clone, Default)]
+pub struct MockState {
+    pub params: Option<AllianceParams>,
+    pub alliances: BTreeMap<String, AllianceAsset>,
+    pub validators: BTreeMap<String, ValidatorResponse>,
+    pub delegations: BTreeMap<DelegationKey, SingleDelegationResponse>,
+    pub rewards: BTreeMap<DelegationKey, Vec<Coin>>,
+    pub reward_rates: BTreeMap<String, Decimal256>,
+    pub unbondings: BTreeMap<String, Vec<UnbondingDelegation>>,
+    pub reward_pools: BTreeMap<String, Decimal256>,
+}
+
+/// Answers `AllianceQuery`s from canned data, for use in contract unit tests
+#[derive(Clone, Default)]
+pub struct MockAllianceQuerier {
+    pub state: MockState,
+}
+
+impl MockAllianceQuerier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_params(mut self, params: AllianceParams) -> Self {
+        self.state.params = Some(params);
+        self
+    }
+
+    pub fn with_alliance(mut self, alliance: AllianceAsset) -> Self {
+        self.state.alliances.insert(alliance.denom.clone(), alliance);
+        self
+    }
+
+    pub fn with_validator(mut self, validator: ValidatorResponse) -> Self {
+        self.state.validators.insert(validator.validator_addr.to_string(), validator);
+        self
+    }
+
+    /// Stores a delegation keyed by delegator, validator and the denom of its balance
+    pub fn with_delegation(
+        mut self,
+        delegator_addr: &str,
+        validator_addr: &str,
+        delegation: SingleDelegationResponse,
+    ) -> Self {
+        let key = (delegator_addr.to_string(), validator_addr.to_string(), delegation.balance.denom.clone());
+        self.state.delegations.insert(key, delegation);
+        self
+    }
+
+    pub fn with_rewards(
+        mut self,
+        delegator_addr: &str,
+        validator_addr: &str,
+        denom: &str,
+        rewards: Vec<Coin>,
+    ) -> Self {
+        let key = (delegator_addr.to_string(), validator_addr.to_string(), denom.to_string());
+        self.state.rewards.insert(key, rewards);
+        self
+    }
+
+    pub fn with_reward_rate(mut self, denom: &str, rate: Decimal256) -> Self {
+        self.state.reward_rates.insert(denom.to_string(), rate);
+        self
+    }
+
+    pub fn with_unbonding(mut self, delegator_addr: &str, unbonding: UnbondingDelegation) -> Self {
+        self.state.unbondings.entry(delegator_addr.to_string()).or_default().push(unbonding);
+        self
+    }
+
+    pub fn with_reward_pool(mut self, denom: &str, balance: Decimal256) -> Self {
+        self.state.reward_pools.insert(denom.to_string(), balance);
+        self
+    }
+
+    pub fn handle(&self, query: &AllianceQuery) -> QuerierResult {
+        handle_alliance_query(query.clone(), &self.state)
+    }
+
+    /// Builds a `MockQuerier` whose custom handler answers alliance queries from this mock
+    pub fn into_mock_querier<C>(self) -> MockQuerier<C>
+    where
+        C: CustomQuery + DeserializeOwned + TryInto<AllianceQuery> + 'static,
+    {
+        MockQuerier::new(&[]).with_custom_handler(move |query: &C| match query.clone().try_into() {
+            Ok(query) => self.handle(&query),
+            Err(_) => SystemResult::Err(SystemError::UnsupportedRequest { kind: "non-alliance custom query".to_string() }),
+        })
+    }
+}
+
+/// Like `cosmwasm_std::testing::mock_dependencies`, but with alliance queries answered by `alliance`
+pub fn mock_dependencies_with_alliance<C>(
+    alliance: MockAllianceQuerier,
+) -> OwnedDeps<MockStorage, MockApi, MockQuerier<C>, C>
+where
+    C: CustomQuery + DeserializeOwned + TryInto<AllianceQuery> + 'static,
+{
+    OwnedDeps {
+        storage: MockStorage::default(),
+        api: MockApi::default(),
+        querier: alliance.into_mock_querier(),
+        custom_query_type: PhantomData,
+    }
+}
+
+/// Answers a single alliance query from `state`
+pub fn handle_alliance_query(query: AllianceQuery, state: &MockState) -> QuerierResult {
+    match &query {
+        AllianceQuery::Alliance { denom } => match state.alliances.get(denom) {
+            Some(alliance) => respond(&AllianceResponse { alliance: alliance.clone() }),
+            None => not_found(format!("alliance {}", denom)),
+        },
+        AllianceQuery::Alliances { pagination } => {
+            let (alliances, pagination) = paginate(&state.alliances, pagination);
+            respond(&AlliancesResponse { alliances, pagination: Some(pagination) })
+        }
+        AllianceQuery::AlliancesDelegations { pagination } => {
+            let (delegations, pagination) = paginate(&state.delegations, pagination);
+            respond(&AlliancesDelegationsResponse { delegations: Some(delegations), pagination: Some(pagination) })
+        }
+        AllianceQuery::AlliancesDelegationsByDelegator { delegator_addr, pagination } => {
+            let matching = state
+                .delegations
+                .iter()
+                .filter(|((delegator, _, _), _)| delegator == delegator_addr.as_str())
+                .map(|(key, delegation)| (key.clone(), delegation.clone()))
+                .collect();
+            let (delegations, pagination) = paginate(&matching, pagination);
+            respond(&AlliancesDelegationsResponse { delegations: Some(delegations), pagination: Some(pagination) })
+        }
+        AllianceQuery::AlliancesDelegationByValidator { delegator_addr, validator_addr, pagination } => {
+            let matching = state
+                .delegations
+                .iter()
+                .filter(|((delegator, validator, _), _)| delegator == delegator_addr.as_str() && validator == validator_addr.as_str())
+                .map(|(key, delegation)| (key.clone(), delegation.clone()))
+                .collect();
+            let (delegations, pagination) = paginate(&matching, pagination);
+            respond(&AlliancesDelegationsResponse { delegations: Some(delegations), pagination: Some(pagination) })
+        }
+        AllianceQuery::Delegation { delegator_addr, validator_addr, denom } => {
+            let key = (delegator_addr.to_string(), validator_addr.to_string(), denom.clone());
+            match state.delegations.get(&key) {
+                Some(delegation) => respond(&DelegationResponse { delegation: delegation.clone() }),
+                None => not_found(format!("delegation {:?}", key)),
+            }
+        }
+        AllianceQuery::DelegationRewards { delegator_addr, validator_addr, denom } => {
+            let key = (delegator_addr.to_string(), validator_addr.to_string(), denom.clone());
+            let rewards = state.rewards.get(&key).cloned().unwrap_or_default();
+            respond(&DelegationRewardsResponse { rewards })
+        }
+        AllianceQuery::Params {} => match &state.params {
+            Some(params) => respond(&ParamsResponse { params: params.clone() }),
+            None => not_found("params".to_string()),
+        },
+        AllianceQuery::Validator { validator_addr } => match state.validators.get(validator_addr.as_str()) {
+            Some(validator) => respond(validator),
+            None => not_found(format!("validator {}", validator_addr)),
+        },
+        AllianceQuery::Validators { pagination } => {
+            let (validators, pagination) = paginate(&state.validators, pagination);
+            respond(&ValidatorsResponse { validators, pagination: Some(pagination) })
+        }
+        AllianceQuery::AllDelegationRewards { delegator_addr, validator_addr } => {
+            let rewards = state
+                .rewards
+                .iter()
+                .filter(|((delegator, validator, _), _)| delegator == delegator_addr.as_str() && validator == validator_addr.as_str())
+                .flat_map(|(_, rewards)| rewards.clone())
+                .collect();
+            respond(&DelegationRewardsResponse { rewards })
+        }
+        AllianceQuery::RewardPool { denom } => match state.reward_pools.get(denom) {
+            Some(balance) => respond(&RewardPoolResponse { denom: denom.clone(), balance: *balance }),
+            None => not_found(format!("reward pool {}", denom)),
+        },
+        AllianceQuery::UnbondingDelegations { delegator_addr, pagination } => {
+            let unbondings = state
+                .unbondings
+                .get(delegator_addr.as_str())
+                .into_iter()
+                .flatten()
+                .cloned()
+                .enumerate()
+                .collect();
+            let (unbondings, pagination) = paginate(&unbondings, pagination);
+            respond(&UnbondingDelegationsResponse { unbondings, pagination: Some(pagination) })
+        }
+        AllianceQuery::AllianceDelegationRewardsRate { denom } => match state.reward_rates.get(denom) {
+            Some(rate) => respond(&RewardRateResponse { denom: denom.clone(), rate: *rate }),
+            None => not_found(format!("reward rate {}", denom)),
+        },
+    }
+}
+
+fn respond<T: Serialize>(response: &T) -> QuerierResult {
+    SystemResult::Ok(to_json_binary(response).into())
+}
+
+fn not_found(what: String) -> QuerierResult {
+    SystemResult::Ok(ContractResult::Err(format!("{} not found", what)))
+}
+
+/// Pages through `items` in key order. The `next_key` is the encoded key of the first item
+/// of the following page.
+fn paginate<K, T>(items: &BTreeMap<K, T>, pagination: &Option<Pagination>) -> (Vec<T>, PaginationResponse)
+where
+    K: Serialize,
+    T: Clone,
+{
+    let pagination = pagination.clone().unwrap_or_default();
+    let keys: Vec<Binary> = items.keys().map(|k| to_json_binary(k).unwrap_or_default()).collect();
+    let start = match &pagination.key {
+        Some(key) => keys.iter().position(|k| k == key).unwrap_or(keys.len()),
+        None => pagination.offset.unwrap_or(0) as usize,
+    };
+    let limit = pagination.limit.map(|l| l as usize).unwrap_or(usize::MAX);
+    let page: Vec<T> = items.values().skip(start).take(limit).cloned().collect();
+    let next_key = keys.get(start + page.len()).cloned();
+    let total = pagination.count_total.unwrap_or(false).then_some(items.len() as u64);
+    (page, PaginationResponse { next_key, total })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{delegation, params};
+    use crate::AllianceQuerier;
+    use cosmwasm_std::{from_json, Addr, QuerierWrapper, StdError};
+
+    /// Pageable items keyed by denom, each holding its position
+    fn keyed_items(denoms: &[&str]) -> BTreeMap<String, usize> {
+        denoms.iter().enumerate().map(|(i, denom)| (denom.to_string(), i)).collect()
+    }
+
+    #[test]
+    fn delegations_can_be_queried_back() {
+        // What a contract test would record after its `Delegate` message went through
+        let stored = delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 100);
+        let mock = MockAllianceQuerier::new().with_delegation("cosmos1delegator", "cosmosvaloper1a", stored);
+        let deps = mock_dependencies_with_alliance::<AllianceCustomQuery>(mock);
+        let querier = deps.as_ref().querier;
+
+        let res = querier
+            .query_alliance_delegation(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1a".into(), "uluna".to_string())
+            .unwrap();
+        assert_eq!(res.delegation, delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 100));
+
+        let res = querier
+            .query_alliance_alliances_delegation_by_validator(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1a".into(), None)
+            .unwrap();
+        assert_eq!(res.delegations, Some(vec![delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 100)]));
+        let res = querier
+            .query_alliance_alliances_delegation_by_validator(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1b".into(), None)
+            .unwrap();
+        assert_eq!(res.delegations, Some(vec![]));
+    }
+
+    #[test]
+    fn params_through_a_querier_wrapper() {
+        let state = MockState { params: Some(params()), ..Default::default() };
+        let querier = MockQuerier::<AllianceCustomQuery>::new(&[])
+            .with_custom_handler(move |query| handle_alliance_query(query.clone().into(), &state));
+        let querier = QuerierWrapper::<AllianceCustomQuery>::new(&querier);
+        assert_eq!(querier.query_alliance_params(), Ok(ParamsResponse { params: params() }));
+    }
+
+    #[test]
+    fn missing_entries_are_reported_as_not_found() {
+        let state = MockState::default();
+        for query in [
+            AllianceQuery::Params {},
+            AllianceQuery::Alliance { denom: "uluna".to_string() },
+            AllianceQuery::Validator { validator_addr: "cosmosvaloper1a".into() },
+            AllianceQuery::RewardPool { denom: "uluna".to_string() },
+        ] {
+            match handle_alliance_query(query.clone(), &state) {
+                SystemResult::Ok(ContractResult::Err(err)) => assert!(err.ends_with("not found"), "{}", err),
+                res => panic!("unexpected {:?} for {:?}", res, query),
+            }
+        }
+
+        let deps = mock_dependencies_with_alliance::<AllianceCustomQuery>(MockAllianceQuerier::new());
+        let err = deps.as_ref().querier.query_alliance_params().unwrap_err();
+        assert!(err.to_string().contains("params not found"), "{}", err);
+        let rewards = deps
+            .as_ref()
+            .querier
+            .query_alliance_delegation_rewards(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1a".into(), "uluna".to_string())
+            .unwrap();
+        assert!(rewards.rewards.is_empty());
+    }
+
+    #[test]
+    fn pages_follow_the_next_key() {
+        let items = keyed_items(&["ua", "ub", "uc"]);
+        let (page, res) = paginate(&items, &Some(Pagination::with_limit(2)));
+        assert_eq!(page.len(), 2);
+        let next_key = res.next_key.unwrap();
+        assert_eq!(from_json::<String>(&next_key).unwrap(), "uc");
+        assert_eq!(res.total, None);
+
+        let (page, res) = paginate(&items, &Some(Pagination { key: Some(next_key), limit: Some(2), ..Default::default() }));
+        assert_eq!(page, vec![2]);
+        assert_eq!(res.next_key, None);
+    }
+
+    #[test]
+    fn pages_respect_offset_and_count_total() {
+        let items = keyed_items(&["ua", "ub", "uc"]);
+        let pagination = Pagination { offset: Some(1), limit: Some(1), count_total: Some(true), ..Default::default() };
+        let (page, res) = paginate(&items, &Some(pagination));
+        assert_eq!(page, vec![1]);
+        assert_eq!(res.total, Some(3));
+
+        let (page, res) = paginate(&items, &None);
+        assert_eq!(page.len(), 3);
+        assert_eq!(res, PaginationResponse { next_key: None, total: None });
+    }
+
+    #[test]
+    fn unknown_page_keys_yield_an_empty_page() {
+        let items = keyed_items(&["ua"]);
+        let pagination = Pagination { key: Some(to_json_binary("zz").unwrap()), ..Default::default() };
+        let (page, res) = paginate(&items, &Some(pagination));
+        assert!(page.is_empty());
+        assert_eq!(res.next_key, None);
+    }
+
+    #[test]
+    fn non_alliance_queries_are_unsupported() {
+        #[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct OtherQuery {}
+        impl CustomQuery for OtherQuery {}
+        impl TryFrom<OtherQuery> for AllianceQuery {
+            type Error = StdError;
+            fn try_from(_: OtherQuery) -> Result<Self, StdError> {
+                Err(StdError::generic_err("not an alliance query"))
+            }
+        }
+
+        let querier = MockAllianceQuerier::new().into_mock_querier::<OtherQuery>();
+        let err = QuerierWrapper::<OtherQuery>::new(&querier).query::<ParamsResponse>(&OtherQuery {}.into()).unwrap_err();
+        assert!(err.to_string().contains("non-alliance custom query"), "{}", err);
+    }
+}
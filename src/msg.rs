@@ -0,0 +1,742 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Attribute, CosmosMsg, CustomMsg, Decimal256, Coin, Uint128, Uint256};
+use std::fmt;
+
+use crate::{AllianceError, DelegatorAddr, ValidatorAddr, WeightRange};
+
+/// A number of Custom messages that can call into the Alliance bindings
+#[cw_serde]
+pub enum AllianceMsg {
+    Delegate {
+        delegator_address: DelegatorAddr,
+        validator_address: ValidatorAddr,
+        amount: Coin,
+    },
+    Undelegate {
+        delegator_address: DelegatorAddr,
+        validator_address: ValidatorAddr,
+        amount: Coin,
+    },
+    Redelegate {
+        delegator_address: DelegatorAddr,
+        validator_src_address: ValidatorAddr,
+        validator_dst_address: ValidatorAddr,
+        amount: Coin,
+    },
+    ClaimDelegationRewards {
+        delegator_address: DelegatorAddr,
+        validator_address: ValidatorAddr,
+        denom: String,
+    },
+    CreateAlliance {
+        denom: String,
+        reward_weight: Decimal256,
+        take_rate: Decimal256,
+        reward_change_rate: Decimal256,
+        reward_change_interval: u64,
+        reward_weight_range: WeightRange,
+    },
+    UpdateAlliance {
+        denom: String,
+        reward_weight: Decimal256,
+        take_rate: Decimal256,
+        reward_change_rate: Decimal256,
+        reward_change_interval: u64,
+        reward_weight_range: WeightRange,
+    },
+    DeleteAlliance {
+        denom: String,
+    },
+}
+
+impl fmt::Display for AllianceMsg {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AllianceMsg::Delegate { validator_address, amount, .. } => {
+                write!(f, "Delegate({} -> {})", amount, validator_address)
+            }
+            AllianceMsg::Undelegate { validator_address, amount, .. } => {
+                write!(f, "Undelegate({} <- {})", amount, validator_address)
+            }
+            AllianceMsg::Redelegate { validator_src_address, validator_dst_address, amount, .. } => {
+                write!(f, "Redelegate({}: {} -> {})", amount, validator_src_address, validator_dst_address)
+            }
+            AllianceMsg::ClaimDelegationRewards { validator_address, denom, .. } => {
+                write!(f, "ClaimDelegationRewards({} @ {})", denom, validator_address)
+            }
+            AllianceMsg::CreateAlliance { denom, .. } => write!(f, "CreateAlliance({})", denom),
+            AllianceMsg::UpdateAlliance { denom, .. } => write!(f, "UpdateAlliance({})", denom),
+            AllianceMsg::DeleteAlliance { denom } => write!(f, "DeleteAlliance({})", denom),
+        }
+    }
+}
+
+impl AllianceMsg {
+    /// Wraps the message in the contract's custom message type and then in `CosmosMsg::Custom`
+    pub fn into_cosmos_msg<T: CustomMsg + From<AllianceMsg>>(self) -> CosmosMsg<T> {
+        CosmosMsg::Custom(T::from(self))
+    }
+
+    /// Event attributes describing the message, for `Response::add_attributes`. The `action` is
+    /// the message name prefixed with `alliance_`, e.g. `alliance_delegate`.
+    pub fn attributes(&self) -> Vec<Attribute> {
+        match self {
+            AllianceMsg::Delegate { delegator_address, validator_address, amount } => vec![
+                Attribute::new("action", "alliance_delegate"),
+                Attribute::new("delegator", delegator_address.as_str()),
+                Attribute::new("validator", validator_address.as_str()),
+                Attribute::new("amount", amount.to_string()),
+            ],
+            AllianceMsg::Undelegate { delegator_address, validator_address, amount } => vec![
+                Attribute::new("action", "alliance_undelegate"),
+                Attribute::new("delegator", delegator_address.as_str()),
+                Attribute::new("validator", validator_address.as_str()),
+                Attribute::new("amount", amount.to_string()),
+            ],
+            AllianceMsg::Redelegate { delegator_address, validator_src_address, validator_dst_address, amount } => vec![
+                Attribute::new("action", "alliance_redelegate"),
+                Attribute::new("delegator", delegator_address.as_str()),
+                Attribute::new("src_validator", validator_src_address.as_str()),
+                Attribute::new("dst_validator", validator_dst_address.as_str()),
+                Attribute::new("amount", amount.to_string()),
+            ],
+            AllianceMsg::ClaimDelegationRewards { delegator_address, validator_address, denom } => vec![
+                Attribute::new("action", "alliance_claim_delegation_rewards"),
+                Attribute::new("delegator", delegator_address.as_str()),
+                Attribute::new("validator", validator_address.as_str()),
+                Attribute::new("denom", denom),
+            ],
+            AllianceMsg::CreateAlliance { denom, .. } => {
+                vec![Attribute::new("action", "alliance_create_alliance"), Attribute::new("denom", denom)]
+            }
+            AllianceMsg::UpdateAlliance { denom, .. } => {
+                vec![Attribute::new("action", "alliance_update_alliance"), Attribute::new("denom", denom)]
+            }
+            AllianceMsg::DeleteAlliance { denom } => {
+                vec![Attribute::new("action", "alliance_delete_alliance"), Attribute::new("denom", denom)]
+            }
+        }
+    }
+}
+
+/// Builds a `Delegate` from a `(delegator, validator, amount)` tuple
+impl From<(Addr, Addr, Coin)> for AllianceMsg {
+    fn from((delegator_address, validator_address, amount): (Addr, Addr, Coin)) -> Self {
+        AllianceMsg::Delegate { delegator_address: delegator_address.into(), validator_address: validator_address.into(), amount }
+    }
+}
+
+/// A ready-made custom message type for contracts that only need alliance messages.
+/// Untagged, so it serializes exactly like the wrapped `AllianceMsg`.
+#[cw_serde]
+#[serde(untagged)]
+#[non_exhaustive]
+pub enum AllianceCustomMsg {
+    Alliance(AllianceMsg),
+}
+
+impl CustomMsg for AllianceCustomMsg {}
+
+impl From<AllianceMsg> for AllianceCustomMsg {
+    fn from(msg: AllianceMsg) -> Self {
+        AllianceCustomMsg::Alliance(msg)
+    }
+}
+
+/// Constructors for `AllianceMsg`, available on any custom message type that converts from it.
+///
+/// Delegators and validators are taken as `DelegatorAddr` and `ValidatorAddr`, so passing typed
+/// addresses in the wrong order does not compile:
+///
+/// ```compile_fail,E0277
+/// use alliance_cosmwasm::{AllianceMsg, CreateAllianceMsg, DelegatorAddr, ValidatorAddr};
+/// use cosmwasm_std::{coin, Addr};
+///
+/// let delegator = DelegatorAddr::from(Addr::unchecked("cosmos1delegator"));
+/// let validator = ValidatorAddr::from("cosmosvaloper1validator");
+/// AllianceMsg::alliance_delegate(validator, delegator, coin(1, "uluna"));
+/// ```
+///
+/// The protection only applies once callers hold the typed values. Both types convert from a plain
+/// `Addr` to ease migration, so swapping two `Addr` arguments still compiles; convert each address
+/// where it is known which one it is, and pass the typed values on from there.
+///
+/// ```
+/// use alliance_cosmwasm::{AllianceMsg, CreateAllianceMsg, DelegatorAddr, ValidatorAddr};
+/// use cosmwasm_std::{coin, Addr};
+///
+/// let delegator = DelegatorAddr::from(Addr::unchecked("cosmos1delegator"));
+/// let validator = ValidatorAddr::from("cosmosvaloper1validator");
+/// AllianceMsg::alliance_delegate(delegator, validator, coin(1, "uluna"));
+/// ```
+pub trait CreateAllianceMsg: From<AllianceMsg> {
+    fn alliance_delegate(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        amount: Coin,
+    ) -> Self {
+        AllianceMsg::Delegate { delegator_address: delegator_address.into(), validator_address: validator_address.into(), amount }.into()
+    }
+
+    /// Like `alliance_delegate`, but rejects coins the chain would refuse
+    fn alliance_delegate_checked(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        amount: Coin,
+    ) -> Result<Self, AllianceError> {
+        let validator_address = validator_address.into();
+        validate_validator_address(&validator_address)?;
+        validate_coin(&amount)?;
+        Ok(Self::alliance_delegate(delegator_address, validator_address, amount))
+    }
+
+    fn alliance_undelegate(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        amount: Coin,
+    ) -> Self {
+        AllianceMsg::Undelegate { delegator_address: delegator_address.into(), validator_address: validator_address.into(), amount }.into()
+    }
+
+    /// One `Undelegate` per coin, all from `validator_address`
+    fn alliance_undelegate_many(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        amounts: Vec<Coin>,
+    ) -> Vec<Self> {
+        let delegator_address = delegator_address.into();
+        let validator_address = validator_address.into();
+        amounts
+            .into_iter()
+            .map(|amount| Self::alliance_undelegate(delegator_address.clone(), validator_address.clone(), amount))
+            .collect()
+    }
+
+    fn alliance_redelegate(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_src_address: impl Into<ValidatorAddr>,
+        validator_dst_address: impl Into<ValidatorAddr>,
+        amount: Coin,
+    ) -> Self {
+        AllianceMsg::Redelegate {
+            delegator_address: delegator_address.into(),
+            validator_src_address: validator_src_address.into(),
+            validator_dst_address: validator_dst_address.into(),
+            amount,
+        }.into()
+    }
+
+    /// Like `alliance_redelegate`, but rejects redelegations the chain would refuse,
+    /// including ones where the source and destination validators are the same
+    fn alliance_redelegate_checked(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_src_address: impl Into<ValidatorAddr>,
+        validator_dst_address: impl Into<ValidatorAddr>,
+        amount: Coin,
+    ) -> Result<Self, AllianceError> {
+        let validator_src_address = validator_src_address.into();
+        let validator_dst_address = validator_dst_address.into();
+        validate_validator_address(&validator_src_address)?;
+        validate_validator_address(&validator_dst_address)?;
+        if validator_src_address == validator_dst_address {
+            return Err(AllianceError::RedelegateToSameValidator { validator: validator_src_address.into_string() });
+        }
+        validate_coin(&amount)?;
+        Ok(Self::alliance_redelegate(delegator_address, validator_src_address, validator_dst_address, amount))
+    }
+
+    /// One `Redelegate` per coin, moving each amount from `validator_src_address` to `validator_dst_address`
+    fn alliance_redelegate_many(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_src_address: impl Into<ValidatorAddr>,
+        validator_dst_address: impl Into<ValidatorAddr>,
+        amounts: Vec<Coin>,
+    ) -> Vec<Self> {
+        let delegator_address = delegator_address.into();
+        let validator_src_address = validator_src_address.into();
+        let validator_dst_address = validator_dst_address.into();
+        amounts
+            .into_iter()
+            .map(|amount| {
+                Self::alliance_redelegate(
+                    delegator_address.clone(),
+                    validator_src_address.clone(),
+                    validator_dst_address.clone(),
+                    amount,
+                )
+            })
+            .collect()
+    }
+
+    fn alliance_claim_delegation_rewards(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        denom: String,
+    ) -> Self {
+        AllianceMsg::ClaimDelegationRewards { delegator_address: delegator_address.into(), validator_address: validator_address.into(), denom }.into()
+    }
+
+    #[deprecated(note = "use `alliance_claim_delegation_rewards` instead")]
+    fn alliance_claim_deligation_rewards(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        denom: String,
+    ) -> Self {
+        Self::alliance_claim_delegation_rewards(delegator_address, validator_address, denom)
+    }
+
+    /// Like `alliance_claim_delegation_rewards`, but rejects denoms the chain would refuse
+    fn alliance_claim_delegation_rewards_checked(
+        delegator_address: impl Into<DelegatorAddr>,
+        validator_address: impl Into<ValidatorAddr>,
+        denom: String,
+    ) -> Result<Self, AllianceError> {
+        let validator_address = validator_address.into();
+        validate_validator_address(&validator_address)?;
+        validate_denom(&denom)?;
+        Ok(Self::alliance_claim_delegation_rewards(delegator_address, validator_address, denom))
+    }
+
+    fn alliance_create(
+        denom: String,
+        reward_weight: Decimal256,
+        take_rate: Decimal256,
+        reward_change_rate: Decimal256,
+        reward_change_interval: u64,
+        reward_weight_range: WeightRange,
+    ) -> Self {
+        AllianceMsg::CreateAlliance {
+            denom,
+            reward_weight,
+            take_rate,
+            reward_change_rate,
+            reward_change_interval,
+            reward_weight_range,
+        }.into()
+    }
+
+    fn alliance_update(
+        denom: String,
+        reward_weight: Decimal256,
+        take_rate: Decimal256,
+        reward_change_rate: Decimal256,
+        reward_change_interval: u64,
+        reward_weight_range: WeightRange,
+    ) -> Self {
+        AllianceMsg::UpdateAlliance {
+            denom,
+            reward_weight,
+            take_rate,
+            reward_change_rate,
+            reward_change_interval,
+            reward_weight_range,
+        }.into()
+    }
+
+    fn alliance_delete(
+        denom: String,
+    ) -> Self {
+        AllianceMsg::DeleteAlliance { denom }.into()
+    }
+
+}
+
+impl<T> CreateAllianceMsg for T where T: From<AllianceMsg> {}
+
+/// Splits `amount` across validators in proportion to their weights, producing one `Delegate`
+/// per validator. Amounts are rounded down and the remainder goes to the last validator with a
+/// non-zero weight, so the delegated total always equals `amount`. Validators whose share rounds
+/// to zero are skipped since the chain rejects empty delegations.
+pub fn split_delegation(
+    delegator_address: impl Into<DelegatorAddr>,
+    amount: Coin,
+    weights: &[(ValidatorAddr, Decimal256)],
+) -> Result<Vec<AllianceMsg>, AllianceError> {
+    let delegator_address = delegator_address.into();
+    let total_weight = weights.iter().try_fold(Decimal256::zero(), |acc, (_, w)| acc.checked_add(*w))?;
+    if total_weight.is_zero() {
+        return Err(AllianceError::InvalidWeights {});
+    }
+    // A non-zero total means there is at least one non-zero weight
+    let last = weights.iter().rposition(|(_, w)| !w.is_zero()).unwrap_or_default();
+    let mut remaining = amount.amount;
+    let mut msgs = vec![];
+    for (i, (validator, weight)) in weights.iter().enumerate() {
+        let share = if i == last {
+            remaining
+        } else {
+            let share = Uint256::from(amount.amount).checked_mul(weight.atomics())? / total_weight.atomics();
+            Uint128::try_from(share)?
+        };
+        remaining -= share;
+        if !share.is_zero() {
+            msgs.push(AllianceMsg::Delegate {
+                delegator_address: delegator_address.clone(),
+                validator_address: validator.clone(),
+                amount: Coin { denom: amount.denom.clone(), amount: share },
+            });
+        }
+    }
+    Ok(msgs)
+}
+
+/// Checks a denom against the Cosmos SDK rules: a letter followed by 2 to 127
+/// alphanumerics or any of `/:._-`
+fn validate_denom(denom: &str) -> Result<(), AllianceError> {
+    if denom.is_empty() {
+        return Err(AllianceError::MissingDenom {});
+    }
+    let mut chars = denom.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && (3..=128).contains(&denom.len())
+        && chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c));
+    if !valid {
+        return Err(AllianceError::InvalidDenom { denom: denom.to_string() });
+    }
+    Ok(())
+}
+
+/// Checks that a validator address has the shape of a bech32 operator address: a prefix ending
+/// in `valoper`, the `1` separator, and a non-empty lowercase alphanumeric data part
+fn validate_validator_address(address: &ValidatorAddr) -> Result<(), AllianceError> {
+    let valid = match address.as_str().rsplit_once('1') {
+        Some((prefix, data)) => {
+            prefix.ends_with("valoper") && !data.is_empty() && data.bytes().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        }
+        None => false,
+    };
+    if !valid {
+        return Err(AllianceError::InvalidValidatorAddress { address: address.to_string() });
+    }
+    Ok(())
+}
+
+fn validate_coin(coin: &Coin) -> Result<(), AllianceError> {
+    validate_denom(&coin.denom)?;
+    if coin.amount.is_zero() {
+        return Err(AllianceError::ZeroAmount {});
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{dec, weight_range};
+    use cosmwasm_std::{from_json, to_json_string};
+
+    fn delegator() -> Addr {
+        Addr::unchecked("cosmos1delegator")
+    }
+
+    // The chain's custom message handler parses these exact tags and field names
+    #[test]
+    fn wire_format_of_each_variant() {
+        let cases = [
+            (
+                AllianceMsg::Delegate {
+                    delegator_address: delegator().into(),
+                    validator_address: "cosmosvaloper1a".into(),
+                    amount: Coin::new(100, "uluna"),
+                },
+                r#"{"delegate":{"delegator_address":"cosmos1delegator","validator_address":"cosmosvaloper1a","amount":{"denom":"uluna","amount":"100"}}}"#,
+            ),
+            (
+                AllianceMsg::Undelegate {
+                    delegator_address: delegator().into(),
+                    validator_address: "cosmosvaloper1a".into(),
+                    amount: Coin::new(100, "uluna"),
+                },
+                r#"{"undelegate":{"delegator_address":"cosmos1delegator","validator_address":"cosmosvaloper1a","amount":{"denom":"uluna","amount":"100"}}}"#,
+            ),
+            (
+                AllianceMsg::Redelegate {
+                    delegator_address: delegator().into(),
+                    validator_src_address: "cosmosvaloper1a".into(),
+                    validator_dst_address: "cosmosvaloper1b".into(),
+                    amount: Coin::new(100, "uluna"),
+                },
+                r#"{"redelegate":{"delegator_address":"cosmos1delegator","validator_src_address":"cosmosvaloper1a","validator_dst_address":"cosmosvaloper1b","amount":{"denom":"uluna","amount":"100"}}}"#,
+            ),
+            (
+                AllianceMsg::ClaimDelegationRewards {
+                    delegator_address: delegator().into(),
+                    validator_address: "cosmosvaloper1a".into(),
+                    denom: "uluna".to_string(),
+                },
+                r#"{"claim_delegation_rewards":{"delegator_address":"cosmos1delegator","validator_address":"cosmosvaloper1a","denom":"uluna"}}"#,
+            ),
+            (
+                AllianceMsg::CreateAlliance {
+                    denom: "uluna".to_string(),
+                    reward_weight: dec("0.1"),
+                    take_rate: dec("0.005"),
+                    reward_change_rate: dec("0.99"),
+                    reward_change_interval: 86_400,
+                    reward_weight_range: weight_range(),
+                },
+                r#"{"create_alliance":{"denom":"uluna","reward_weight":"0.1","take_rate":"0.005","reward_change_rate":"0.99","reward_change_interval":86400,"reward_weight_range":{"min":"0.05","max":"0.2"}}}"#,
+            ),
+            (
+                AllianceMsg::UpdateAlliance {
+                    denom: "uluna".to_string(),
+                    reward_weight: dec("0.1"),
+                    take_rate: dec("0.005"),
+                    reward_change_rate: dec("0.99"),
+                    reward_change_interval: 86_400,
+                    reward_weight_range: weight_range(),
+                },
+                r#"{"update_alliance":{"denom":"uluna","reward_weight":"0.1","take_rate":"0.005","reward_change_rate":"0.99","reward_change_interval":86400,"reward_weight_range":{"min":"0.05","max":"0.2"}}}"#,
+            ),
+            (
+                AllianceMsg::DeleteAlliance { denom: "uluna".to_string() },
+                r#"{"delete_alliance":{"denom":"uluna"}}"#,
+            ),
+        ];
+        for (msg, json) in cases {
+            assert_eq!(to_json_string(&msg).unwrap(), json);
+            assert_eq!(from_json::<AllianceMsg>(json.as_bytes()).unwrap(), msg);
+        }
+    }
+
+    fn delegated(msgs: &[AllianceMsg]) -> Vec<(String, u128)> {
+        msgs.iter()
+            .map(|msg| match msg {
+                AllianceMsg::Delegate { validator_address, amount, .. } => (validator_address.to_string(), amount.amount.u128()),
+                msg => panic!("unexpected {}", msg),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_delegation_preserves_the_total() {
+        let weights = [
+            (ValidatorAddr::from("a"), dec("0.3")),
+            (ValidatorAddr::from("b"), dec("0.3")),
+            (ValidatorAddr::from("c"), dec("0.4")),
+        ];
+        let msgs = split_delegation(delegator(), Coin::new(1_000_001, "uluna"), &weights).unwrap();
+        assert_eq!(
+            delegated(&msgs),
+            vec![("a".to_string(), 300_000), ("b".to_string(), 300_000), ("c".to_string(), 400_001)]
+        );
+        assert_eq!(delegated(&msgs).iter().map(|(_, a)| a).sum::<u128>(), 1_000_001);
+    }
+
+    #[test]
+    fn split_delegation_gives_the_remainder_to_the_last_weighted_validator() {
+        let weights = [
+            (ValidatorAddr::from("a"), dec("1")),
+            (ValidatorAddr::from("b"), dec("1")),
+            (ValidatorAddr::from("c"), dec("0")),
+        ];
+        let msgs = split_delegation(delegator(), Coin::new(101, "uluna"), &weights).unwrap();
+        assert_eq!(delegated(&msgs), vec![("a".to_string(), 50), ("b".to_string(), 51)]);
+    }
+
+    #[test]
+    fn split_delegation_skips_shares_that_round_to_zero() {
+        let weights = [(ValidatorAddr::from("a"), dec("1")), (ValidatorAddr::from("b"), dec("1000"))];
+        let msgs = split_delegation(delegator(), Coin::new(100, "uluna"), &weights).unwrap();
+        assert_eq!(delegated(&msgs), vec![("b".to_string(), 100)]);
+    }
+
+    #[test]
+    fn split_delegation_rejects_zero_weights() {
+        assert_eq!(split_delegation(delegator(), Coin::new(100, "uluna"), &[]), Err(AllianceError::InvalidWeights {}));
+        let weights = [(ValidatorAddr::from("a"), Decimal256::zero())];
+        assert_eq!(split_delegation(delegator(), Coin::new(100, "uluna"), &weights), Err(AllianceError::InvalidWeights {}));
+    }
+
+    const IBC_DENOM: &str = "ibc/B3504E092456BA618CC28AC671A71FB08C6CA0FD0BE7C8A5B5A3E2DD933CC9E4";
+
+    fn delegate(amount: u128) -> AllianceMsg {
+        AllianceMsg::Delegate {
+            delegator_address: delegator().into(),
+            validator_address: "cosmosvaloper1a".into(),
+            amount: Coin::new(amount, "uluna"),
+        }
+    }
+
+    #[test]
+    fn governance_constructors() {
+        let create = AllianceMsg::alliance_create("uluna".to_string(), dec("0.1"), dec("0.005"), dec("0.99"), 86_400, weight_range());
+        assert_eq!(
+            create,
+            AllianceMsg::CreateAlliance {
+                denom: "uluna".to_string(),
+                reward_weight: dec("0.1"),
+                take_rate: dec("0.005"),
+                reward_change_rate: dec("0.99"),
+                reward_change_interval: 86_400,
+                reward_weight_range: weight_range(),
+            }
+        );
+        let update = AllianceMsg::alliance_update("uluna".to_string(), dec("0.2"), dec("0"), dec("1"), 3_600, weight_range());
+        assert_eq!(
+            update,
+            AllianceMsg::UpdateAlliance {
+                denom: "uluna".to_string(),
+                reward_weight: dec("0.2"),
+                take_rate: Decimal256::zero(),
+                reward_change_rate: Decimal256::one(),
+                reward_change_interval: 3_600,
+                reward_weight_range: weight_range(),
+            }
+        );
+        assert_eq!(AllianceMsg::alliance_delete("uluna".to_string()), AllianceMsg::DeleteAlliance { denom: "uluna".to_string() });
+    }
+
+    #[test]
+    fn delegate_checked_validates_the_coin() {
+        assert_eq!(AllianceMsg::alliance_delegate_checked(delegator(), "cosmosvaloper1a", Coin::new(100, "uluna")), Ok(delegate(100)));
+        assert_eq!(
+            AllianceMsg::alliance_delegate_checked(delegator(), "cosmosvaloper1a", Coin::new(0, "uluna")),
+            Err(AllianceError::ZeroAmount {})
+        );
+        assert_eq!(
+            AllianceMsg::alliance_delegate_checked(delegator(), "cosmosvaloper1a", Coin::new(100, "")),
+            Err(AllianceError::MissingDenom {})
+        );
+    }
+
+    #[test]
+    fn claim_checked_validates_the_denom() {
+        for denom in ["uluna", IBC_DENOM, "factory/cosmos1abc/token", "a.b:c_d-e"] {
+            assert!(AllianceMsg::alliance_claim_delegation_rewards_checked(delegator(), "cosmosvaloper1a", denom.to_string()).is_ok(), "{}", denom);
+        }
+        assert_eq!(
+            AllianceMsg::alliance_claim_delegation_rewards_checked(delegator(), "cosmosvaloper1a", String::new()),
+            Err(AllianceError::MissingDenom {})
+        );
+        let too_long = format!("u{}", "a".repeat(128));
+        for denom in ["ul", "1uluna", "/uluna", "ulu na", "ulúna", too_long.as_str()] {
+            assert_eq!(
+                AllianceMsg::alliance_claim_delegation_rewards_checked(delegator(), "cosmosvaloper1a", denom.to_string()),
+                Err(AllianceError::InvalidDenom { denom: denom.to_string() })
+            );
+        }
+    }
+
+    #[test]
+    fn checked_constructors_validate_validator_addresses() {
+        for address in ["cosmosvaloper1a", "terravaloper1qx9s3zcf8t0w2d", "valoper1x"] {
+            assert!(AllianceMsg::alliance_delegate_checked(delegator(), address, Coin::new(100, "uluna")).is_ok(), "{}", address);
+        }
+        for address in ["", "cosmos1abc", "cosmosvaloper", "cosmosvaloper1", "cosmosvaloper1ABC", "cosmosvaloper1a b"] {
+            let invalid = Err(AllianceError::InvalidValidatorAddress { address: address.to_string() });
+            assert_eq!(AllianceMsg::alliance_delegate_checked(delegator(), address, Coin::new(100, "uluna")), invalid, "{}", address);
+            assert_eq!(
+                AllianceMsg::alliance_claim_delegation_rewards_checked(delegator(), address, "uluna".to_string()),
+                invalid,
+                "{}",
+                address
+            );
+            assert_eq!(
+                AllianceMsg::alliance_redelegate_checked(delegator(), "cosmosvaloper1a", address, Coin::new(100, "uluna")),
+                invalid,
+                "{}",
+                address
+            );
+        }
+        // A malformed address is reported before comparing source and destination
+        assert_eq!(
+            AllianceMsg::alliance_redelegate_checked(delegator(), "cosmos1abc", "cosmos1abc", Coin::new(100, "uluna")),
+            Err(AllianceError::InvalidValidatorAddress { address: "cosmos1abc".to_string() })
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_claim_spelling_builds_the_same_message() {
+        assert_eq!(
+            AllianceMsg::alliance_claim_deligation_rewards(delegator(), "cosmosvaloper1a", "uluna".to_string()),
+            AllianceMsg::alliance_claim_delegation_rewards(delegator(), "cosmosvaloper1a", "uluna".to_string())
+        );
+    }
+
+    #[test]
+    fn redelegate_checked_rejects_the_same_validator() {
+        assert_eq!(
+            AllianceMsg::alliance_redelegate_checked(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", Coin::new(100, "uluna")),
+            Ok(AllianceMsg::alliance_redelegate(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", Coin::new(100, "uluna")))
+        );
+        assert_eq!(
+            AllianceMsg::alliance_redelegate_checked(delegator(), "cosmosvaloper1a", "cosmosvaloper1a", Coin::new(100, "uluna")),
+            Err(AllianceError::RedelegateToSameValidator { validator: "cosmosvaloper1a".to_string() })
+        );
+        assert_eq!(
+            AllianceMsg::alliance_redelegate_checked(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", Coin::new(0, "uluna")),
+            Err(AllianceError::ZeroAmount {})
+        );
+    }
+
+    #[test]
+    fn many_builds_one_message_per_coin() {
+        let coins = vec![Coin::new(1, "uluna"), Coin::new(2, IBC_DENOM)];
+        let msgs = AllianceMsg::alliance_undelegate_many(delegator(), "cosmosvaloper1a", coins.clone());
+        assert_eq!(
+            msgs,
+            vec![
+                AllianceMsg::alliance_undelegate(delegator(), "cosmosvaloper1a", coins[0].clone()),
+                AllianceMsg::alliance_undelegate(delegator(), "cosmosvaloper1a", coins[1].clone()),
+            ]
+        );
+        let msgs = AllianceMsg::alliance_redelegate_many(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", coins.clone());
+        assert_eq!(
+            msgs,
+            vec![
+                AllianceMsg::alliance_redelegate(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", coins[0].clone()),
+                AllianceMsg::alliance_redelegate(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", coins[1].clone()),
+            ]
+        );
+        assert!(AllianceMsg::alliance_undelegate_many(delegator(), "cosmosvaloper1a", vec![]).is_empty());
+    }
+
+    #[test]
+    fn delegate_from_tuple() {
+        let msg = AllianceMsg::from((delegator(), Addr::unchecked("cosmosvaloper1a"), Coin::new(100, "uluna")));
+        assert_eq!(msg, delegate(100));
+    }
+
+    #[test]
+    fn display_summarizes_the_message() {
+        assert_eq!(delegate(100).to_string(), "Delegate(100uluna -> cosmosvaloper1a)");
+        let msg = AllianceMsg::alliance_redelegate(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", Coin::new(5, "uluna"));
+        assert_eq!(msg.to_string(), "Redelegate(5uluna: cosmosvaloper1a -> cosmosvaloper1b)");
+        assert_eq!(AllianceMsg::alliance_delete("uluna".to_string()).to_string(), "DeleteAlliance(uluna)");
+    }
+
+    #[test]
+    fn attributes_name_the_action() {
+        assert_eq!(
+            delegate(100).attributes(),
+            vec![
+                Attribute::new("action", "alliance_delegate"),
+                Attribute::new("delegator", "cosmos1delegator"),
+                Attribute::new("validator", "cosmosvaloper1a"),
+                Attribute::new("amount", "100uluna"),
+            ]
+        );
+        let msg = AllianceMsg::alliance_redelegate(delegator(), "cosmosvaloper1a", "cosmosvaloper1b", Coin::new(5, "uluna"));
+        assert_eq!(msg.attributes()[0], Attribute::new("action", "alliance_redelegate"));
+        assert_eq!(msg.attributes()[3], Attribute::new("dst_validator", "cosmosvaloper1b"));
+        assert_eq!(
+            AllianceMsg::alliance_delete("uluna".to_string()).attributes(),
+            vec![Attribute::new("action", "alliance_delete_alliance"), Attribute::new("denom", "uluna")]
+        );
+    }
+
+    #[test]
+    fn custom_msg_serializes_like_the_wrapped_message() {
+        let custom = AllianceCustomMsg::alliance_delegate(delegator(), "cosmosvaloper1a", Coin::new(100, "uluna"));
+        assert_eq!(to_json_string(&custom).unwrap(), to_json_string(&delegate(100)).unwrap());
+        assert_eq!(from_json::<AllianceCustomMsg>(to_json_string(&delegate(100)).unwrap().as_bytes()).unwrap(), custom);
+
+        let msg: CosmosMsg<AllianceCustomMsg> = delegate(100).into_cosmos_msg();
+        assert_eq!(msg, CosmosMsg::Custom(AllianceCustomMsg::Alliance(delegate(100))));
+        // The wrapped message still rejects fields it does not know
+        from_json::<AllianceCustomMsg>(br#"{"delegate":{"delegator_address":"cosmos1delegator","validator_address":"cosmosvaloper1a","amount":{"denom":"uluna","amount":"100"},"memo":"x"}}"#)
+            .unwrap_err();
+    }
+}
@@ -0,0 +1,879 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Binary, CustomQuery, QuerierWrapper, StdError, StdResult};
+
+use crate::{
+    AllianceAsset, AllianceResponse, AlliancesDelegationsResponse, AlliancesResponse, DecCoin, DelegationResponse,
+    DelegationRewardsResponse, DelegatorAddr, DelegatorPortfolio, Pagination, ParamsResponse, RewardPoolResponse,
+    RewardRateResponse, UnbondingDelegationsResponse, ValidatorAddr, ValidatorResponse,
+    ValidatorsResponse,
+};
+
+/// Alliance-specific queries
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum AllianceQuery {
+    #[returns(AllianceResponse)]
+    Alliance { denom: String },
+
+    #[returns(AlliancesResponse)]
+    Alliances { pagination: Option<Pagination> },
+
+    #[returns(AlliancesDelegationsResponse)]
+    AlliancesDelegations { pagination: Option<Pagination> },
+
+    #[returns(AlliancesDelegationsResponse)]
+    AlliancesDelegationsByDelegator {
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    },
+
+    #[returns(AlliancesDelegationsResponse)]
+    AlliancesDelegationByValidator {
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        pagination: Option<Pagination>,
+    },
+
+    #[returns(DelegationResponse)]
+    Delegation {
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    },
+
+    #[returns(DelegationRewardsResponse)]
+    DelegationRewards {
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    },
+
+    #[returns(ParamsResponse)]
+    Params {},
+
+    #[returns(ValidatorResponse)]
+    Validator { validator_addr: ValidatorAddr },
+
+    #[returns(ValidatorsResponse)]
+    Validators { pagination: Option<Pagination> },
+
+    #[returns(DelegationRewardsResponse)]
+    AllDelegationRewards {
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+    },
+
+    #[returns(RewardPoolResponse)]
+    RewardPool { denom: String },
+
+    #[returns(UnbondingDelegationsResponse)]
+    UnbondingDelegations {
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    },
+
+    #[returns(RewardRateResponse)]
+    AllianceDelegationRewardsRate { denom: String },
+}
+
+/// A ready-made custom query type for contracts that only need alliance queries, so that
+/// `QuerierWrapper<AllianceQueryWrapper>` implements `AllianceQuerier` out of the box.
+/// Serializes exactly like the wrapped `AllianceQuery`.
+#[cw_serde]
+pub struct AllianceQueryWrapper(pub AllianceQuery);
+
+impl CustomQuery for AllianceQueryWrapper {}
+
+impl From<AllianceQuery> for AllianceQueryWrapper {
+    fn from(query: AllianceQuery) -> Self {
+        AllianceQueryWrapper(query)
+    }
+}
+
+impl From<AllianceQueryWrapper> for AllianceQuery {
+    fn from(query: AllianceQueryWrapper) -> Self {
+        query.0
+    }
+}
+
+/// The largest page size the auto-paginating helpers will request, to keep each
+/// query within the chain's query gas limit
+pub const MAX_PAGE_LIMIT: u64 = 100;
+
+pub trait AllianceQuerier {
+    fn query_alliance_alliance(
+        &self,
+        denom: String,
+    ) -> StdResult<AllianceResponse>;
+
+    fn query_alliance_alliances(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesResponse>;
+
+    fn query_alliance_alliances_delegations(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse>;
+
+    fn query_alliance_alliances_delegations_by_delegator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse>;
+
+    fn query_alliance_alliances_delegation_by_validator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse>;
+
+    fn query_alliance_delegation(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationResponse>;
+
+    fn query_alliance_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationRewardsResponse>;
+
+    fn query_alliance_params(
+        &self,
+    ) -> StdResult<ParamsResponse>;
+
+    fn query_alliance_validator(
+        &self,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<ValidatorResponse>;
+
+    fn query_alliance_validators(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<ValidatorsResponse>;
+
+    fn query_alliance_all_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<DelegationRewardsResponse>;
+
+    fn query_alliance_reward_pool(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardPoolResponse>;
+
+    fn query_alliance_unbonding_delegations(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<UnbondingDelegationsResponse>;
+
+    fn query_alliance_alliance_delegation_rewards_rate(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardRateResponse>;
+
+    /// Like `query_alliance_delegation`, but returns `None` when the delegation does not exist.
+    /// An error is treated as "not found" when it is a `StdError::NotFound` or its message
+    /// contains "not found" (case-insensitive), which is how the alliance module reports it.
+    fn try_query_alliance_delegation(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<Option<DelegationResponse>> {
+        match self.query_alliance_delegation(delegator_addr, validator_addr, denom) {
+            Ok(res) => Ok(Some(res)),
+            Err(err) if is_not_found(&err) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Queries the rewards for each `(validator, denom)` pair, in order. Fails on the first error.
+    fn query_alliance_delegation_rewards_batch(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pairs: &[(ValidatorAddr, String)],
+    ) -> StdResult<Vec<DelegationRewardsResponse>> {
+        pairs
+            .iter()
+            .map(|(validator_addr, denom)| {
+                self.query_alliance_delegation_rewards(delegator_addr.clone(), validator_addr.clone(), denom.clone())
+            })
+            .collect()
+    }
+
+    /// Like `query_alliance_alliance`, but returns the bare `AllianceAsset`
+    fn query_alliance_asset(
+        &self,
+        denom: String,
+    ) -> StdResult<AllianceAsset> {
+        self.query_alliance_alliance(denom).map(AllianceAsset::from)
+    }
+
+    /// Queries each alliance in order. The error of the first failing query names its denom.
+    fn query_alliance_alliances_by_denoms(
+        &self,
+        denoms: &[String],
+    ) -> StdResult<Vec<AllianceAsset>> {
+        denoms
+            .iter()
+            .map(|denom| {
+                self.query_alliance_alliance(denom.clone())
+                    .map(|res| res.alliance)
+                    .map_err(|err| StdError::generic_err(format!("Querying alliance {} failed: {}", denom, err)))
+            })
+            .collect()
+    }
+
+    /// Like `query_alliance_alliances_by_denoms`, but keeps going past failures and
+    /// returns each denom alongside its own result
+    fn query_alliance_alliances_by_denoms_lenient(
+        &self,
+        denoms: &[String],
+    ) -> Vec<(String, StdResult<AllianceAsset>)> {
+        denoms
+            .iter()
+            .map(|denom| (denom.clone(), self.query_alliance_alliance(denom.clone()).map(|res| res.alliance)))
+            .collect()
+    }
+
+    /// Queries a validator and returns its `total_staked` in `denom`, failing with `NotFound`
+    /// when the validator has no stake in that denom
+    fn query_alliance_validator_denom(
+        &self,
+        validator_addr: ValidatorAddr,
+        denom: &str,
+    ) -> StdResult<DecCoin> {
+        let res = self.query_alliance_validator(validator_addr.clone())?;
+        if !res.total_staked.iter().any(|c| c.denom.as_deref() == Some(denom)) {
+            return Err(StdError::not_found(format!("{} stake with validator {}", denom, validator_addr)));
+        }
+        Ok(DecCoin { denom: Some(denom.to_string()), amount: res.total_staked_of(denom) })
+    }
+
+    /// Walks every page of `Validators`, following `next_key` until the chain reports no more pages.
+    /// Page sizes are capped at `MAX_PAGE_LIMIT`.
+    fn query_all_alliance_validators(
+        &self,
+        page_size: u64,
+    ) -> StdResult<Vec<ValidatorResponse>> {
+        let mut validators = vec![];
+        let mut key = None;
+        loop {
+            let pagination = Pagination { key, limit: Some(page_size.min(MAX_PAGE_LIMIT)), ..Default::default() };
+            let res = self.query_alliance_validators(Some(pagination))?;
+            // An empty page with a next_key would otherwise loop forever
+            let empty_page = res.validators.is_empty();
+            validators.extend(res.validators);
+            key = res.pagination.and_then(|p| p.next_key).filter(|k| !k.is_empty());
+            if key.is_none() || empty_page {
+                break;
+            }
+        }
+        Ok(validators)
+    }
+
+    /// Folds over every delegation made by `delegator_addr`, paging through
+    /// `AlliancesDelegationsByDelegator` without buffering all of them
+    fn fold_delegations<B>(
+        &self,
+        delegator_addr: DelegatorAddr,
+        init: B,
+        mut f: impl FnMut(B, &DelegationResponse) -> B,
+    ) -> StdResult<B>
+    where
+        Self: Sized,
+    {
+        let mut acc = init;
+        let mut key = None;
+        loop {
+            let pagination = Pagination { key, limit: Some(MAX_PAGE_LIMIT), ..Default::default() };
+            let res = self.query_alliance_alliances_delegations_by_delegator(delegator_addr.clone(), Some(pagination))?;
+            key = res.pagination.and_then(|p| p.next_key).filter(|k| !k.is_empty());
+            let delegations = res.delegations.unwrap_or_default();
+            let empty_page = delegations.is_empty();
+            for delegation in delegations {
+                acc = f(acc, &DelegationResponse { delegation });
+            }
+            if key.is_none() || empty_page {
+                break;
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Collects every delegation made by `delegator_addr` via `fold_delegations`, along with
+    /// the balances summed per denom
+    fn query_delegator_portfolio(
+        &self,
+        delegator_addr: DelegatorAddr,
+    ) -> StdResult<DelegatorPortfolio>
+    where
+        Self: Sized,
+    {
+        let delegations = self.fold_delegations(delegator_addr.clone(), vec![], |mut acc, res| {
+            acc.push(res.delegation.clone());
+            acc
+        })?;
+        Ok(DelegatorPortfolio::new(delegator_addr.into(), delegations))
+    }
+
+    /// Queries a page of validators with `count_total` forced on, returning the total alongside the page
+    fn query_alliance_validators_with_total(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<(Vec<ValidatorResponse>, Option<u64>)> {
+        let pagination = Pagination { count_total: Some(true), ..pagination.unwrap_or_default() };
+        let res = self.query_alliance_validators(Some(pagination))?;
+        Ok((res.validators, res.pagination.and_then(|p| p.total)))
+    }
+
+    /// Lazily walks the `Alliances` pages, issuing one query per call to `next()`.
+    /// Page sizes are capped at `MAX_PAGE_LIMIT`.
+    fn alliances_pages(
+        &self,
+        page_size: u64,
+    ) -> AlliancesPager<'_, Self>
+    where
+        Self: Sized,
+    {
+        AlliancesPager { querier: self, page_size: page_size.min(MAX_PAGE_LIMIT), key: None, done: false }
+    }
+}
+
+/// Whether the chain answers alliance queries, checked with a lightweight `Params` query.
+/// Lets contracts degrade gracefully on chains without the alliance module.
+pub fn probe_alliance_support<Q: AllianceQuerier + ?Sized>(querier: &Q) -> bool {
+    querier.query_alliance_params().is_ok()
+}
+
+fn is_not_found(err: &StdError) -> bool {
+    match err {
+        StdError::NotFound { .. } => true,
+        err => err.to_string().to_lowercase().contains("not found"),
+    }
+}
+
+/// Iterator over pages of `AllianceAsset`s, created by `AllianceQuerier::alliances_pages`
+pub struct AlliancesPager<'a, Q: AllianceQuerier + ?Sized> {
+    querier: &'a Q,
+    page_size: u64,
+    key: Option<Binary>,
+    done: bool,
+}
+
+impl<'a, Q: AllianceQuerier + ?Sized> Iterator for AlliancesPager<'a, Q> {
+    type Item = StdResult<Vec<AllianceAsset>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let pagination = Pagination { key: self.key.take(), limit: Some(self.page_size), ..Default::default() };
+        match self.querier.query_alliance_alliances(Some(pagination)) {
+            Ok(res) => {
+                self.key = res.pagination.and_then(|p| p.next_key).filter(|k| !k.is_empty());
+                self.done = self.key.is_none() || res.alliances.is_empty();
+                Some(Ok(res.alliances))
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+impl<'a, T> AllianceQuerier for QuerierWrapper<'a, T>
+where
+    T: CustomQuery + From<AllianceQuery>,
+{
+    fn query_alliance_alliance(
+        &self,
+        denom: String,
+    ) -> StdResult<AllianceResponse> {
+        let custom_query: T = AllianceQuery::Alliance { denom }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_alliances(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesResponse> {
+        let custom_query: T = AllianceQuery::Alliances { pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_alliances_delegations(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        let custom_query: T = AllianceQuery::AlliancesDelegations { pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_alliances_delegations_by_delegator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        let custom_query: T = AllianceQuery::AlliancesDelegationsByDelegator { delegator_addr, pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_alliances_delegation_by_validator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        let custom_query: T = AllianceQuery::AlliancesDelegationByValidator { delegator_addr, validator_addr, pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_delegation(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationResponse> {
+        let custom_query: T = AllianceQuery::Delegation { delegator_addr, validator_addr, denom }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationRewardsResponse> {
+        let custom_query: T = AllianceQuery::DelegationRewards { delegator_addr, validator_addr, denom }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_params(
+        &self,
+    ) -> StdResult<ParamsResponse> {
+        let custom_query: T = AllianceQuery::Params { }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_validator(
+        &self,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<ValidatorResponse> {
+        let custom_query: T = AllianceQuery::Validator { validator_addr }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_validators(
+        &self,
+        pagination: Option<Pagination>
+    ) -> StdResult<ValidatorsResponse> {
+        let custom_query: T = AllianceQuery::Validators { pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_all_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<DelegationRewardsResponse> {
+        let custom_query: T = AllianceQuery::AllDelegationRewards { delegator_addr, validator_addr }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_reward_pool(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardPoolResponse> {
+        let custom_query: T = AllianceQuery::RewardPool { denom }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_unbonding_delegations(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<UnbondingDelegationsResponse> {
+        let custom_query: T = AllianceQuery::UnbondingDelegations { delegator_addr, pagination }.into();
+        self.query(&custom_query.into())
+    }
+
+    fn query_alliance_alliance_delegation_rewards_rate(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardRateResponse> {
+        let custom_query: T = AllianceQuery::AllianceDelegationRewardsRate { denom }.into();
+        self.query(&custom_query.into())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{alliance_asset, delegation, params, validator};
+    use crate::mock::{mock_dependencies_with_alliance, MockAllianceQuerier};
+    use crate::PaginationResponse;
+    use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        from_json, to_json_binary, to_json_string, Addr, Coin, ContractResult, Decimal256, OwnedDeps, SystemResult,
+        Uint128,
+    };
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::rc::Rc;
+
+    type AllianceDeps = OwnedDeps<MockStorage, MockApi, MockQuerier<AllianceQueryWrapper>, AllianceQueryWrapper>;
+
+    fn deps(mock: MockAllianceQuerier) -> AllianceDeps {
+        mock_dependencies_with_alliance(mock)
+    }
+
+    fn validator_with_stake(addr: &str, staked: &[(&str, u128)]) -> ValidatorResponse {
+        ValidatorResponse {
+            total_staked: staked
+                .iter()
+                .map(|(denom, amount)| DecCoin { denom: Some(denom.to_string()), amount: Decimal256::from_ratio(*amount, 1u128) })
+                .collect(),
+            ..validator(addr)
+        }
+    }
+
+    fn with_alliances(denoms: &[&str]) -> MockAllianceQuerier {
+        denoms.iter().fold(MockAllianceQuerier::new(), |mock, denom| mock.with_alliance(alliance_asset(denom)))
+    }
+
+    fn with_validators(count: usize) -> MockAllianceQuerier {
+        (0..count).fold(MockAllianceQuerier::new(), |mock, i| {
+            mock.with_validator(validator_with_stake(&format!("cosmosvaloper1v{}", i), &[("uluna", 100)]))
+        })
+    }
+
+    type Recorded = Rc<RefCell<Vec<AllianceQuery>>>;
+
+    /// A querier answering from `mock` that also records every query it receives
+    fn recording(mock: MockAllianceQuerier) -> (MockQuerier<AllianceQueryWrapper>, Recorded) {
+        let queries = Recorded::default();
+        let recorded = queries.clone();
+        let querier = MockQuerier::new(&[]).with_custom_handler(move |query: &AllianceQueryWrapper| {
+            recorded.borrow_mut().push(query.0.clone());
+            mock.handle(&query.0)
+        });
+        (querier, queries)
+    }
+
+    fn limits(queries: &Recorded) -> Vec<Option<u64>> {
+        queries
+            .borrow()
+            .iter()
+            .map(|query| match query {
+                AllianceQuery::Alliances { pagination } | AllianceQuery::Validators { pagination } => {
+                    pagination.as_ref().and_then(|p| p.limit)
+                }
+                query => panic!("unexpected {:?}", query),
+            })
+            .collect()
+    }
+
+    fn with_delegation(mock: MockAllianceQuerier, delegator: &str, validator: &str, denom: &str, amount: u128) -> MockAllianceQuerier {
+        mock.with_delegation(delegator, validator, delegation(delegator, validator, denom, amount))
+    }
+
+    // `cw_serde` names variants and fields in snake_case, which is what the chain parses
+    #[test]
+    fn queries_use_snake_case() {
+        let query: AllianceQuery = from_json(
+            br#"{"delegation":{"delegator_addr":"cosmos1delegator","validator_addr":"cosmosvaloper1a","denom":"uluna"}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            query,
+            AllianceQuery::Delegation {
+                delegator_addr: Addr::unchecked("cosmos1delegator").into(),
+                validator_addr: "cosmosvaloper1a".into(),
+                denom: "uluna".to_string(),
+            }
+        );
+        assert_eq!(from_json::<AllianceQuery>(br#"{"params":{}}"#).unwrap(), AllianceQuery::Params {});
+    }
+
+    #[test]
+    fn camel_case_queries_are_rejected() {
+        from_json::<AllianceQuery>(
+            br#"{"delegation":{"delegatorAddr":"cosmos1delegator","validatorAddr":"cosmosvaloper1a","denom":"uluna"}}"#,
+        )
+        .unwrap_err();
+        from_json::<AllianceQuery>(br#"{"allDelegationRewards":{"delegator_addr":"cosmos1delegator","validator_addr":"cosmosvaloper1a"}}"#)
+            .unwrap_err();
+        // `params` is a single word, so only a capitalized tag differs from snake_case
+        from_json::<AllianceQuery>(br#"{"Params":{}}"#).unwrap_err();
+    }
+
+    #[test]
+    fn fold_delegations_pages_and_keeps_only_the_delegators() {
+        // More delegations than fit on one page of `MAX_PAGE_LIMIT`
+        let mut mock = MockAllianceQuerier::new();
+        for i in 0..150 {
+            mock = with_delegation(mock, "cosmos1alice", &format!("cosmosvaloper1v{:03}", i), "uluna", 10);
+        }
+        mock = with_delegation(mock, "cosmos1bob", "cosmosvaloper1v000", "uluna", 1000);
+        let deps = deps(mock);
+
+        let (count, total) = deps
+            .as_ref()
+            .querier
+            .fold_delegations(Addr::unchecked("cosmos1alice").into(), (0, Uint128::zero()), |(count, total), d| {
+                (count + 1, total + d.delegation.balance.amount)
+            })
+            .unwrap();
+        assert_eq!(count, 150);
+        assert_eq!(total, Uint128::new(1500));
+    }
+
+    #[test]
+    fn fold_delegations_only_queries_the_delegators_pages() {
+        let mut mock = MockAllianceQuerier::new();
+        for i in 0..150 {
+            mock = with_delegation(mock, "cosmos1alice", &format!("cosmosvaloper1v{:03}", i), "uluna", 10);
+        }
+        let (querier, queries) = recording(with_delegation(mock, "cosmos1bob", "cosmosvaloper1v000", "uluna", 1000));
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        querier.fold_delegations(Addr::unchecked("cosmos1alice").into(), (), |_, _| ()).unwrap();
+
+        let queries = queries.borrow();
+        assert_eq!(queries.len(), 2);
+        for query in queries.iter() {
+            assert!(
+                matches!(query, AllianceQuery::AlliancesDelegationsByDelegator { delegator_addr, .. } if delegator_addr.as_str() == "cosmos1alice"),
+                "{:?}",
+                query
+            );
+        }
+    }
+
+    // Implementations outside this crate are used through trait objects too
+    #[test]
+    fn alliance_querier_is_object_safe() {
+        let deps = deps(MockAllianceQuerier::new().with_params(params()));
+        let querier: &dyn AllianceQuerier = &deps.as_ref().querier;
+        assert_eq!(querier.query_alliance_params(), Ok(ParamsResponse { params: params() }));
+        assert!(probe_alliance_support(querier));
+    }
+
+    #[test]
+    fn all_validators_are_collected_across_pages() {
+        let (querier, queries) = recording(with_validators(3));
+        let validators = QuerierWrapper::<AllianceQueryWrapper>::new(&querier).query_all_alliance_validators(2).unwrap();
+        assert_eq!(
+            validators.iter().map(|v| v.validator_addr.as_str()).collect::<Vec<_>>(),
+            ["cosmosvaloper1v0", "cosmosvaloper1v1", "cosmosvaloper1v2"]
+        );
+        assert_eq!(limits(&queries), [Some(2), Some(2)]);
+    }
+
+    #[test]
+    fn all_validators_stops_on_an_empty_page_with_a_next_key() {
+        let calls = Rc::new(RefCell::new(0));
+        let counter = calls.clone();
+        let querier = MockQuerier::<AllianceQueryWrapper>::new(&[]).with_custom_handler(move |_| {
+            *counter.borrow_mut() += 1;
+            let res = ValidatorsResponse {
+                validators: vec![],
+                pagination: Some(PaginationResponse { next_key: Some(Binary::from(b"more".as_slice())), total: None }),
+            };
+            SystemResult::Ok(ContractResult::Ok(to_json_binary(&res).unwrap()))
+        });
+        let validators = QuerierWrapper::<AllianceQueryWrapper>::new(&querier).query_all_alliance_validators(10).unwrap();
+        assert!(validators.is_empty());
+        assert_eq!(*calls.borrow(), 1);
+    }
+
+    #[test]
+    fn alliances_pages_yields_one_page_per_query() {
+        let (querier, queries) = recording(with_alliances(&["ua", "ub", "uc", "ud", "ue"]));
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        let mut pages = querier.alliances_pages(2);
+        assert!(queries.borrow().is_empty());
+
+        let denoms = |page: Vec<AllianceAsset>| page.into_iter().map(|a| a.denom).collect::<Vec<_>>();
+        assert_eq!(denoms(pages.next().unwrap().unwrap()), ["ua", "ub"]);
+        assert_eq!(queries.borrow().len(), 1);
+        assert_eq!(denoms(pages.next().unwrap().unwrap()), ["uc", "ud"]);
+        assert_eq!(denoms(pages.next().unwrap().unwrap()), ["ue"]);
+        assert!(pages.next().is_none());
+        assert_eq!(queries.borrow().len(), 3);
+    }
+
+    #[test]
+    fn alliances_pages_stops_after_an_error() {
+        let querier = MockQuerier::<AllianceQueryWrapper>::new(&[]);
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        let mut pages = querier.alliances_pages(2);
+        assert!(pages.next().unwrap().is_err());
+        assert!(pages.next().is_none());
+    }
+
+    #[test]
+    fn page_sizes_are_capped() {
+        let (querier, queries) = recording(with_validators(1).with_alliance(alliance_asset("uluna")));
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        querier.query_all_alliance_validators(MAX_PAGE_LIMIT + 1).unwrap();
+        querier.alliances_pages(u64::MAX).for_each(|page| drop(page.unwrap()));
+        assert_eq!(limits(&queries), [Some(MAX_PAGE_LIMIT), Some(MAX_PAGE_LIMIT)]);
+    }
+
+    #[test]
+    fn validators_with_total_forces_count_total() {
+        let (querier, queries) = recording(with_validators(3));
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        let (validators, total) = querier.query_alliance_validators_with_total(Some(Pagination::with_limit(2))).unwrap();
+        assert_eq!(validators.len(), 2);
+        assert_eq!(total, Some(3));
+        assert_eq!(
+            queries.borrow()[0],
+            AllianceQuery::Validators { pagination: Some(Pagination { limit: Some(2), count_total: Some(true), ..Default::default() }) }
+        );
+    }
+
+    #[test]
+    fn try_query_delegation_maps_not_found_to_none() {
+        let mock = with_delegation(MockAllianceQuerier::new(), "cosmos1delegator", "cosmosvaloper1a", "uluna", 10);
+        let deps = deps(mock);
+        let querier = deps.as_ref().querier;
+        let found = querier.try_query_alliance_delegation(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1a".into(), "uluna".to_string());
+        assert_eq!(found, Ok(Some(DelegationResponse { delegation: delegation("cosmos1delegator", "cosmosvaloper1a", "uluna", 10) })));
+        let missing = querier.try_query_alliance_delegation(Addr::unchecked("cosmos1delegator").into(), "cosmosvaloper1b".into(), "uluna".to_string());
+        assert_eq!(missing, Ok(None));
+    }
+
+    #[test]
+    fn try_query_delegation_passes_other_errors_through() {
+        let querier = MockQuerier::<AllianceQueryWrapper>::new(&[])
+            .with_custom_handler(|_| SystemResult::Ok(ContractResult::Err("connection reset".to_string())));
+        let res = QuerierWrapper::<AllianceQueryWrapper>::new(&querier).try_query_alliance_delegation(
+            Addr::unchecked("cosmos1delegator").into(),
+            "cosmosvaloper1a".into(),
+            "uluna".to_string(),
+        );
+        assert!(res.unwrap_err().to_string().contains("connection reset"));
+    }
+
+    #[test]
+    fn delegation_rewards_batch_keeps_the_input_order() {
+        let mock = MockAllianceQuerier::new()
+            .with_rewards("cosmos1delegator", "cosmosvaloper1a", "uluna", vec![Coin::new(1, "uluna")])
+            .with_rewards("cosmos1delegator", "cosmosvaloper1b", "uatom", vec![Coin::new(2, "uluna")])
+            .with_rewards("cosmos1delegator", "cosmosvaloper1c", "uluna", vec![Coin::new(3, "uluna")]);
+        let deps = deps(mock);
+        let pairs = [
+            (ValidatorAddr::from("cosmosvaloper1c"), "uluna".to_string()),
+            (ValidatorAddr::from("cosmosvaloper1a"), "uluna".to_string()),
+            (ValidatorAddr::from("cosmosvaloper1b"), "uatom".to_string()),
+        ];
+        let rewards = deps.as_ref().querier.query_alliance_delegation_rewards_batch(Addr::unchecked("cosmos1delegator").into(), &pairs).unwrap();
+        assert_eq!(rewards.iter().map(|r| r.rewards[0].amount.u128()).collect::<Vec<_>>(), [3, 1, 2]);
+    }
+
+    #[test]
+    fn delegation_rewards_batch_fails_on_the_first_error() {
+        let querier = MockQuerier::<AllianceQueryWrapper>::new(&[]);
+        let pairs = [(ValidatorAddr::from("cosmosvaloper1a"), "uluna".to_string())];
+        QuerierWrapper::<AllianceQueryWrapper>::new(&querier)
+            .query_alliance_delegation_rewards_batch(Addr::unchecked("cosmos1delegator").into(), &pairs)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn alliances_by_denoms_keeps_the_input_order() {
+        let deps = deps(with_alliances(&["ua", "ub", "uc"]));
+        let denoms = ["uc".to_string(), "ua".to_string(), "ub".to_string()];
+        let alliances = deps.as_ref().querier.query_alliance_alliances_by_denoms(&denoms).unwrap();
+        assert_eq!(alliances, vec![alliance_asset("uc"), alliance_asset("ua"), alliance_asset("ub")]);
+    }
+
+    #[test]
+    fn alliances_by_denoms_names_the_failing_denom() {
+        let deps = deps(with_alliances(&["ua", "uc"]));
+        let denoms = ["ua".to_string(), "ub".to_string(), "uc".to_string()];
+        let err = deps.as_ref().querier.query_alliance_alliances_by_denoms(&denoms).unwrap_err();
+        assert!(err.to_string().contains("Querying alliance ub failed"), "{}", err);
+
+        let results = deps.as_ref().querier.query_alliance_alliances_by_denoms_lenient(&denoms);
+        assert_eq!(results.iter().map(|(denom, _)| denom.as_str()).collect::<Vec<_>>(), ["ua", "ub", "uc"]);
+        assert_eq!(results[0].1, Ok(alliance_asset("ua")));
+        assert!(results[1].1.is_err());
+        assert_eq!(results[2].1, Ok(alliance_asset("uc")));
+    }
+
+    #[test]
+    fn alliance_asset_is_unwrapped() {
+        let deps = deps(with_alliances(&["uluna"]));
+        assert_eq!(deps.as_ref().querier.query_alliance_asset("uluna".to_string()), Ok(alliance_asset("uluna")));
+        deps.as_ref().querier.query_alliance_asset("uatom".to_string()).unwrap_err();
+    }
+
+    #[test]
+    fn params_through_the_ready_made_query_type() {
+        let querier: MockQuerier<AllianceQueryWrapper> = MockAllianceQuerier::new().with_params(params()).into_mock_querier();
+        let querier = QuerierWrapper::<AllianceQueryWrapper>::new(&querier);
+        assert_eq!(querier.query_alliance_params(), Ok(ParamsResponse { params: params() }));
+        assert_eq!(AllianceQuery::from(AllianceQueryWrapper::from(AllianceQuery::Params {})), AllianceQuery::Params {});
+        assert_eq!(to_json_string(&AllianceQueryWrapper(AllianceQuery::Params {})).unwrap(), r#"{"params":{}}"#);
+    }
+
+    #[test]
+    fn all_delegation_rewards_wire_format() {
+        let query = AllianceQuery::AllDelegationRewards {
+            delegator_addr: Addr::unchecked("cosmos1delegator").into(),
+            validator_addr: "cosmosvaloper1a".into(),
+        };
+        let json = r#"{"all_delegation_rewards":{"delegator_addr":"cosmos1delegator","validator_addr":"cosmosvaloper1a"}}"#;
+        assert_eq!(to_json_string(&query).unwrap(), json);
+        assert_eq!(from_json::<AllianceQuery>(json.as_bytes()).unwrap(), query);
+    }
+
+    #[test]
+    fn probe_alliance_support_checks_params() {
+        let supported = deps(MockAllianceQuerier::new().with_params(params()));
+        assert!(probe_alliance_support(&supported.as_ref().querier));
+
+        let unsupported = MockQuerier::<AllianceQueryWrapper>::new(&[]);
+        assert!(!probe_alliance_support(&QuerierWrapper::<AllianceQueryWrapper>::new(&unsupported)));
+    }
+
+    #[test]
+    fn validator_denom_extracts_one_entry() {
+        let deps = deps(MockAllianceQuerier::new().with_validator(validator_with_stake("cosmosvaloper1a", &[("uluna", 100), ("uatom", 7)])));
+        let querier = deps.as_ref().querier;
+        assert_eq!(
+            querier.query_alliance_validator_denom("cosmosvaloper1a".into(), "uatom"),
+            Ok(DecCoin { denom: Some("uatom".to_string()), amount: Decimal256::from_ratio(7u128, 1u128) })
+        );
+        let err = querier.query_alliance_validator_denom("cosmosvaloper1a".into(), "uosmo").unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }), "{}", err);
+        querier.query_alliance_validator_denom("cosmosvaloper1b".into(), "uluna").unwrap_err();
+    }
+
+    #[test]
+    fn delegator_portfolio_sums_balances() {
+        let mut mock = with_delegation(MockAllianceQuerier::new(), "cosmos1alice", "cosmosvaloper1a", "uluna", 10);
+        mock = with_delegation(mock, "cosmos1alice", "cosmosvaloper1b", "uluna", 5);
+        mock = with_delegation(mock, "cosmos1alice", "cosmosvaloper1b", "uatom", 3);
+        mock = with_delegation(mock, "cosmos1bob", "cosmosvaloper1a", "uluna", 1000);
+        let deps = deps(mock);
+
+        let portfolio = deps.as_ref().querier.query_delegator_portfolio(Addr::unchecked("cosmos1alice").into()).unwrap();
+        assert_eq!(portfolio.delegator_addr, Addr::unchecked("cosmos1alice"));
+        assert_eq!(portfolio.delegations.len(), 3);
+        assert_eq!(portfolio.balances, BTreeMap::from([("uatom".to_string(), Uint128::new(3)), ("uluna".to_string(), Uint128::new(15))]));
+    }
+}
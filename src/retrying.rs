@@ -0,0 +1,207 @@
+use cosmwasm_std::StdResult;
+
+use crate::{
+    AllianceQuerier, AllianceResponse, AlliancesDelegationsResponse, AlliancesResponse, DelegationResponse,
+    DelegationRewardsResponse, DelegatorAddr, Pagination, ParamsResponse, RewardPoolResponse, RewardRateResponse,
+    UnbondingDelegationsResponse, ValidatorAddr, ValidatorResponse, ValidatorsResponse,
+};
+
+/// Wraps an `AllianceQuerier` and retries failed queries up to `retries` more times, returning
+/// the last error if every attempt fails. All alliance queries are idempotent, so this is safe.
+/// On-chain queries are deterministic within a block and a retry will fail the same way, so this
+/// is mainly useful for off-chain tooling and test harnesses talking to flaky RPC endpoints.
+pub struct RetryingAllianceQuerier<Q: AllianceQuerier> {
+    inner: Q,
+    retries: u32,
+}
+
+impl<Q: AllianceQuerier> RetryingAllianceQuerier<Q> {
+    pub fn new(inner: Q, retries: u32) -> Self {
+        RetryingAllianceQuerier { inner, retries }
+    }
+
+    pub fn inner(&self) -> &Q {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> Q {
+        self.inner
+    }
+
+    fn retry<R>(&self, query: impl Fn(&Q) -> StdResult<R>) -> StdResult<R> {
+        let mut attempts_left = self.retries;
+        loop {
+            match query(&self.inner) {
+                Err(_) if attempts_left > 0 => attempts_left -= 1,
+                res => return res,
+            }
+        }
+    }
+}
+
+impl<Q: AllianceQuerier> AllianceQuerier for RetryingAllianceQuerier<Q> {
+    fn query_alliance_alliance(
+        &self,
+        denom: String,
+    ) -> StdResult<AllianceResponse> {
+        self.retry(|q| q.query_alliance_alliance(denom.clone()))
+    }
+
+    fn query_alliance_alliances(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesResponse> {
+        self.retry(|q| q.query_alliance_alliances(pagination.clone()))
+    }
+
+    fn query_alliance_alliances_delegations(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.retry(|q| q.query_alliance_alliances_delegations(pagination.clone()))
+    }
+
+    fn query_alliance_alliances_delegations_by_delegator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.retry(|q| q.query_alliance_alliances_delegations_by_delegator(delegator_addr.clone(), pagination.clone()))
+    }
+
+    fn query_alliance_alliances_delegation_by_validator(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<AlliancesDelegationsResponse> {
+        self.retry(|q| {
+            q.query_alliance_alliances_delegation_by_validator(delegator_addr.clone(), validator_addr.clone(), pagination.clone())
+        })
+    }
+
+    fn query_alliance_delegation(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationResponse> {
+        self.retry(|q| q.query_alliance_delegation(delegator_addr.clone(), validator_addr.clone(), denom.clone()))
+    }
+
+    fn query_alliance_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+        denom: String,
+    ) -> StdResult<DelegationRewardsResponse> {
+        self.retry(|q| q.query_alliance_delegation_rewards(delegator_addr.clone(), validator_addr.clone(), denom.clone()))
+    }
+
+    fn query_alliance_params(
+        &self,
+    ) -> StdResult<ParamsResponse> {
+        self.retry(|q| q.query_alliance_params())
+    }
+
+    fn query_alliance_validator(
+        &self,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<ValidatorResponse> {
+        self.retry(|q| q.query_alliance_validator(validator_addr.clone()))
+    }
+
+    fn query_alliance_validators(
+        &self,
+        pagination: Option<Pagination>,
+    ) -> StdResult<ValidatorsResponse> {
+        self.retry(|q| q.query_alliance_validators(pagination.clone()))
+    }
+
+    fn query_alliance_all_delegation_rewards(
+        &self,
+        delegator_addr: DelegatorAddr,
+        validator_addr: ValidatorAddr,
+    ) -> StdResult<DelegationRewardsResponse> {
+        self.retry(|q| q.query_alliance_all_delegation_rewards(delegator_addr.clone(), validator_addr.clone()))
+    }
+
+    fn query_alliance_reward_pool(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardPoolResponse> {
+        self.retry(|q| q.query_alliance_reward_pool(denom.clone()))
+    }
+
+    fn query_alliance_unbonding_delegations(
+        &self,
+        delegator_addr: DelegatorAddr,
+        pagination: Option<Pagination>,
+    ) -> StdResult<UnbondingDelegationsResponse> {
+        self.retry(|q| q.query_alliance_unbonding_delegations(delegator_addr.clone(), pagination.clone()))
+    }
+
+    fn query_alliance_alliance_delegation_rewards_rate(
+        &self,
+        denom: String,
+    ) -> StdResult<RewardRateResponse> {
+        self.retry(|q| q.query_alliance_alliance_delegation_rewards_rate(denom.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockAllianceQuerier;
+    use crate::fixtures::params;
+    use crate::AllianceQueryWrapper;
+    use cosmwasm_std::testing::MockQuerier;
+    use cosmwasm_std::{ContractResult, QuerierWrapper, SystemResult};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// A querier whose first `failures` queries fail, with later ones answered from a mock
+    fn flaky(failures: u32) -> (MockQuerier<AllianceQueryWrapper>, Rc<Cell<u32>>) {
+        let calls = Rc::new(Cell::new(0));
+        let counter = calls.clone();
+        let mock = MockAllianceQuerier::new().with_params(params());
+        let querier = MockQuerier::new(&[]).with_custom_handler(move |query: &AllianceQueryWrapper| {
+            counter.set(counter.get() + 1);
+            if counter.get() <= failures {
+                return SystemResult::Ok(ContractResult::Err(format!("attempt {} failed", counter.get())));
+            }
+            mock.handle(&query.0)
+        });
+        (querier, calls)
+    }
+
+    #[test]
+    fn succeeds_after_two_failures() {
+        let (querier, calls) = flaky(2);
+        let querier = RetryingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier), 2);
+        assert_eq!(querier.query_alliance_params(), Ok(ParamsResponse { params: params() }));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn returns_the_last_error_once_retries_run_out() {
+        let (querier, calls) = flaky(3);
+        let querier = RetryingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier), 2);
+        let err = querier.query_alliance_params().unwrap_err();
+        assert!(err.to_string().contains("attempt 3 failed"), "{}", err);
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[test]
+    fn does_not_retry_successful_queries() {
+        let (querier, calls) = flaky(0);
+        let querier = RetryingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier), 5);
+        querier.query_alliance_params().unwrap();
+        assert_eq!(calls.get(), 1);
+
+        let (querier, calls) = flaky(1);
+        let querier = RetryingAllianceQuerier::new(QuerierWrapper::<AllianceQueryWrapper>::new(&querier), 0);
+        querier.query_alliance_params().unwrap_err();
+        assert_eq!(calls.get(), 1);
+    }
+}
@@ -0,0 +1,211 @@
+//! Minimal RFC3339 support for the timestamps the alliance module emits,
+//! e.g. "2023-06-06T18:37:29.956787974Z".
+
+const NANOS_PER_SECOND: i128 = 1_000_000_000;
+const SECONDS_PER_DAY: i128 = 86_400;
+
+/// Formats nanoseconds since the epoch in UTC the way chrono's `DateTime<Utc>` serializes, which
+/// this crate used before: the fraction is omitted when zero, and otherwise written with 3, 6 or 9
+/// digits, whichever is the shortest exact form.
+pub(crate) fn format(nanos: u64) -> String {
+    let secs = nanos / 1_000_000_000;
+    let subsec = (nanos % 1_000_000_000) as u32;
+    let (year, month, day) = civil_from_days((secs / 86_400) as i64);
+    let secs_of_day = secs % 86_400;
+    let fraction = match subsec {
+        0 => String::new(),
+        n if n % 1_000_000 == 0 => format!(".{:03}", n / 1_000_000),
+        n if n % 1_000 == 0 => format!(".{:06}", n / 1_000),
+        n => format!(".{:09}", n),
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        fraction,
+    )
+}
+
+/// Parses an RFC3339 date-time into nanoseconds since the epoch. The result may be negative or
+/// exceed `u64::MAX`; range checks are left to the caller. Digits beyond nanosecond precision
+/// are truncated.
+pub(crate) fn parse(s: &str) -> Result<i128, String> {
+    let invalid = || format!("invalid RFC3339 timestamp: {}", s);
+    let b = s.as_bytes();
+    if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || !matches!(b[10], b'T' | b't') || b[13] != b':' || b[16] != b':' {
+        return Err(invalid());
+    }
+    let year = digits(&b[0..4]).ok_or_else(invalid)?;
+    let month = digits(&b[5..7]).ok_or_else(invalid)?;
+    let day = digits(&b[8..10]).ok_or_else(invalid)?;
+    let hour = digits(&b[11..13]).ok_or_else(invalid)?;
+    let minute = digits(&b[14..16]).ok_or_else(invalid)?;
+    let second = digits(&b[17..19]).ok_or_else(invalid)?;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) || hour > 23 || minute > 59 || second > 59 {
+        return Err(invalid());
+    }
+
+    let mut rest = &b[19..];
+    let mut subsec_nanos = 0;
+    if rest.first() == Some(&b'.') {
+        let len = rest[1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if len == 0 {
+            return Err(invalid());
+        }
+        let fraction = &rest[1..1 + len];
+        let kept = &fraction[..len.min(9)];
+        subsec_nanos = digits(kept).ok_or_else(invalid)? * 10_i128.pow(9 - kept.len() as u32);
+        rest = &rest[1 + len..];
+    }
+
+    let offset_secs = match rest {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let hours = digits(&[*h1, *h2]).ok_or_else(invalid)?;
+            let minutes = digits(&[*m1, *m2]).ok_or_else(invalid)?;
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if *sign == b'+' { offset } else { -offset }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let days = days_from_civil(year as i64, month as u32, day as u32) as i128;
+    let secs = days * SECONDS_PER_DAY + hour * 3600 + minute * 60 + second - offset_secs;
+    Ok(secs * NANOS_PER_SECOND + subsec_nanos)
+}
+
+fn digits(b: &[u8]) -> Option<i128> {
+    b.iter().try_fold(0, |acc, c| c.is_ascii_digit().then(|| acc * 10 + (c - b'0') as i128))
+}
+
+fn days_in_month(year: i128, month: i128) -> i128 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Conversions between civil dates and days since the epoch, after Howard Hinnant's
+// `days_from_civil`/`civil_from_days` algorithms.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = if year >= 0 { year } else { year - 399 } / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = if days >= 0 { days } else { days - 146_096 } / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE_NANOS: u64 = 1_686_076_649_956_787_974;
+
+    // Expected strings are what the chrono-based serializer produced for the same timestamps
+    #[test]
+    fn formats_like_chrono() {
+        assert_eq!(format(EXAMPLE_NANOS), "2023-06-06T18:37:29.956787974Z");
+        assert_eq!(format(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format(1_686_076_649_000_000_000), "2023-06-06T18:37:29Z");
+        assert_eq!(format(1_686_076_649_100_000_000), "2023-06-06T18:37:29.100Z");
+        assert_eq!(format(1_686_076_649_123_000_000), "2023-06-06T18:37:29.123Z");
+        assert_eq!(format(1_686_076_649_123_400_000), "2023-06-06T18:37:29.123400Z");
+        assert_eq!(format(1_686_076_649_000_001_000), "2023-06-06T18:37:29.000001Z");
+        assert_eq!(format(1_686_076_649_100_000_010), "2023-06-06T18:37:29.100000010Z");
+        assert_eq!(format(1_686_076_649_000_000_001), "2023-06-06T18:37:29.000000001Z");
+        assert_eq!(format(u64::MAX), "2554-07-21T23:34:33.709551615Z");
+    }
+
+    #[test]
+    fn parses_the_documented_example() {
+        assert_eq!(parse("2023-06-06T18:37:29.956787974Z"), Ok(EXAMPLE_NANOS as i128));
+        assert_eq!(parse("2023-06-06t18:37:29.956787974z"), Ok(EXAMPLE_NANOS as i128));
+    }
+
+    #[test]
+    fn parses_zero_and_trailing_zero_fractions() {
+        assert_eq!(parse("1970-01-01T00:00:00Z"), Ok(0));
+        assert_eq!(parse("2023-06-06T18:37:29Z"), Ok(1_686_076_649_000_000_000));
+        assert_eq!(parse("2023-06-06T18:37:29.000000000Z"), Ok(1_686_076_649_000_000_000));
+        assert_eq!(parse("2023-06-06T18:37:29.100Z"), Ok(1_686_076_649_100_000_000));
+        assert_eq!(parse("2023-06-06T18:37:29.1Z"), Ok(1_686_076_649_100_000_000));
+    }
+
+    #[test]
+    fn handles_leap_days() {
+        assert_eq!(parse("2024-02-29T00:00:00Z"), Ok(1_709_164_800 * NANOS_PER_SECOND));
+        assert_eq!(parse("2000-02-29T00:00:00Z"), Ok(951_782_400 * NANOS_PER_SECOND));
+        assert!(parse("2023-02-29T00:00:00Z").is_err());
+        assert!(parse("1900-02-29T00:00:00Z").is_err());
+        assert_eq!(format(1_709_164_800_000_000_000), "2024-02-29T00:00:00Z");
+        assert_eq!(format(951_782_400_000_000_000), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn applies_offsets() {
+        assert_eq!(parse("2023-06-06T20:37:29.956787974+02:00"), Ok(EXAMPLE_NANOS as i128));
+        assert_eq!(parse("2023-06-06T13:07:29.956787974-05:30"), Ok(EXAMPLE_NANOS as i128));
+        assert_eq!(parse("2023-06-06T18:37:29.956787974+00:00"), Ok(EXAMPLE_NANOS as i128));
+        assert!(parse("2023-06-06T18:37:29+24:00").is_err());
+        assert!(parse("2023-06-06T18:37:29+02").is_err());
+    }
+
+    // Range checks are left to the caller, which rejects anything outside a `u64` of nanoseconds
+    #[test]
+    fn reports_timestamps_outside_the_u64_range() {
+        assert_eq!(parse("1969-12-31T23:59:59Z"), Ok(-NANOS_PER_SECOND));
+        assert_eq!(parse("1970-01-01T00:00:00+00:01"), Ok(-60 * NANOS_PER_SECOND));
+        assert_eq!(parse("2554-07-21T23:34:33.709551615Z"), Ok(u64::MAX as i128));
+        assert_eq!(parse("2554-07-21T23:34:33.709551616Z"), Ok(u64::MAX as i128 + 1));
+        assert!(parse("9999-12-31T23:59:59Z").unwrap() > u64::MAX as i128);
+    }
+
+    #[test]
+    fn truncates_digits_beyond_nanoseconds() {
+        assert_eq!(parse("2023-06-06T18:37:29.9567879741Z"), Ok(EXAMPLE_NANOS as i128));
+        assert_eq!(parse("2023-06-06T18:37:29.956787974999999Z"), Ok(EXAMPLE_NANOS as i128));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for s in [
+            "",
+            "2023-06-06",
+            "2023-06-06 18:37:29Z",
+            "2023-06-06T18:37:29",
+            "2023-06-06T18:37:29.Z",
+            "2023-13-06T18:37:29Z",
+            "2023-06-31T18:37:29Z",
+            "2023-06-06T24:00:00Z",
+            "2023-06-06T18:60:29Z",
+            "2023-06-06T18:37:60Z",
+            "2023-06-06T18:37:29ZZ",
+            "+023-06-06T18:37:29Z",
+        ] {
+            assert!(parse(s).is_err(), "{}", s);
+        }
+    }
+}